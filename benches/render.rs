@@ -0,0 +1,263 @@
+use std::hint::black_box;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use egui::epaint::{ImageDelta, Primitive};
+use egui::{
+    ClippedPrimitive, Color32, ColorImage, Context, Mesh, Pos2, Rect, TextureId, TextureOptions,
+    TexturesDelta, Ui,
+};
+use skia_safe::surfaces::raster_n32_premul;
+use smol_egui_skia::{EguiSkia, Painter, RasterizeOptions, rasterize_ui};
+
+const SIZES: [(i32, i32); 3] = [(512, 512), (1024, 768), (1920, 1080)];
+const PIXELS_PER_POINT: [f32; 3] = [1.0, 1.5, 2.0];
+
+#[derive(Clone, Copy)]
+enum Scene {
+    DemoWindows,
+    ColorTest,
+}
+
+impl Scene {
+    const ALL: [Scene; 2] = [Scene::DemoWindows, Scene::ColorTest];
+
+    fn name(self) -> &'static str {
+        match self {
+            Scene::DemoWindows => "demo_windows",
+            Scene::ColorTest => "color_test",
+        }
+    }
+
+    fn ui(self) -> Box<dyn FnMut(&mut Ui)> {
+        match self {
+            Scene::DemoWindows => {
+                let mut demo = egui_demo_lib::DemoWindows::default();
+                Box::new(move |ui| demo.ui(ui))
+            }
+            Scene::ColorTest => {
+                let mut demo = egui_demo_lib::ColorTest::default();
+                Box::new(move |ui| demo.ui(ui))
+            }
+        }
+    }
+}
+
+/// Input for a screen of `size` pixels at `pixels_per_point`.
+fn input(size: (i32, i32), pixels_per_point: f32) -> egui::RawInput {
+    let mut input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            Pos2::default(),
+            egui::vec2(size.0 as f32, size.1 as f32) / pixels_per_point,
+        )),
+        ..Default::default()
+    };
+    input
+        .viewports
+        .entry(input.viewport_id)
+        .or_default()
+        .native_pixels_per_point = Some(pixels_per_point);
+    input
+}
+
+/// Runs `scene` for a couple of frames so windows are laid out, and returns the
+/// tessellated last frame together with all texture changes.
+fn settled_frame(
+    scene: Scene,
+    size: (i32, i32),
+    pixels_per_point: f32,
+) -> (Vec<ClippedPrimitive>, TexturesDelta) {
+    let ctx = Context::default();
+    let mut ui = scene.ui();
+    let mut textures_delta = TexturesDelta::default();
+    let mut output = ctx.run_ui(input(size, pixels_per_point), &mut ui);
+    textures_delta.append(output.textures_delta);
+    output = ctx.run_ui(input(size, pixels_per_point), &mut ui);
+    textures_delta.append(output.textures_delta);
+    let primitives = ctx.tessellate(output.shapes, pixels_per_point);
+    (primitives, textures_delta)
+}
+
+fn run_ui(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run_ui");
+    for scene in Scene::ALL {
+        for pixels_per_point in PIXELS_PER_POINT {
+            let mut backend = EguiSkia::new(pixels_per_point);
+            let mut ui = scene.ui();
+            let input = input((1024, 768), pixels_per_point);
+            backend.run_ui(input.clone(), &mut ui);
+
+            group.bench_with_input(
+                BenchmarkId::new(scene.name(), pixels_per_point),
+                &input,
+                |b, input| b.iter(|| black_box(backend.run_ui(input.clone(), &mut ui))),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn tessellate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tessellate");
+    for scene in Scene::ALL {
+        for pixels_per_point in PIXELS_PER_POINT {
+            let ctx = Context::default();
+            let mut ui = scene.ui();
+            ctx.run_ui(input((1024, 768), pixels_per_point), &mut ui);
+            let shapes = ctx
+                .run_ui(input((1024, 768), pixels_per_point), &mut ui)
+                .shapes;
+
+            group.bench_with_input(
+                BenchmarkId::new(scene.name(), pixels_per_point),
+                &shapes,
+                |b, shapes| b.iter(|| black_box(ctx.tessellate(shapes.clone(), pixels_per_point))),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn paint_meshes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("paint_meshes");
+    for scene in Scene::ALL {
+        for pixels_per_point in PIXELS_PER_POINT {
+            let size = (1024, 768);
+            let (primitives, textures_delta) = settled_frame(scene, size, pixels_per_point);
+            let mut surface = raster_n32_premul(size).unwrap();
+            let mut painter = Painter::new();
+            // Upload the font atlas once, so only the mesh conversion is measured.
+            painter.paint_and_update_textures(
+                surface.canvas(),
+                pixels_per_point,
+                Vec::new(),
+                textures_delta,
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new(scene.name(), pixels_per_point),
+                &primitives,
+                |b, primitives| {
+                    b.iter(|| {
+                        painter.paint_and_update_textures(
+                            surface.canvas(),
+                            pixels_per_point,
+                            primitives.clone(),
+                            TexturesDelta::default(),
+                        )
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn texture_upload(c: &mut Criterion) {
+    const ATLAS_SIZE: usize = 2048;
+    const PATCH_SIZE: usize = 64;
+
+    let id = TextureId::Managed(1);
+    let options = TextureOptions::LINEAR;
+
+    // Draw the texture once per upload so the skia image is rebuilt as well.
+    let mut mesh = Mesh::with_texture(id);
+    mesh.add_rect_with_uv(
+        Rect::from_min_size(Pos2::ZERO, egui::vec2(256.0, 256.0)),
+        Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+        Color32::WHITE,
+    );
+    let primitives = vec![ClippedPrimitive {
+        clip_rect: Rect::EVERYTHING,
+        primitive: Primitive::Mesh(mesh),
+    }];
+
+    let delta = |image_delta: ImageDelta| {
+        let mut textures_delta = TexturesDelta::default();
+        textures_delta
+            .set
+            .insert(id, [image_delta].into_iter().collect());
+        textures_delta
+    };
+
+    let atlas = ColorImage::filled([ATLAS_SIZE, ATLAS_SIZE], Color32::GRAY);
+    let patch = ColorImage::filled([PATCH_SIZE, PATCH_SIZE], Color32::RED);
+
+    let mut surface = raster_n32_premul((256, 256)).unwrap();
+    let mut painter = Painter::new();
+
+    let mut group = c.benchmark_group("texture_upload");
+    group.bench_function("full", |b| {
+        b.iter(|| {
+            painter.paint_and_update_textures(
+                surface.canvas(),
+                1.0,
+                primitives.clone(),
+                delta(ImageDelta::full(atlas.clone(), options)),
+            )
+        })
+    });
+    group.bench_function("partial", |b| {
+        painter.paint_and_update_textures(
+            surface.canvas(),
+            1.0,
+            Vec::new(),
+            delta(ImageDelta::full(atlas.clone(), options)),
+        );
+        let mut n = 0;
+        b.iter(|| {
+            let slots = ATLAS_SIZE / PATCH_SIZE;
+            let pos = [(n % slots) * PATCH_SIZE, (n / slots % slots) * PATCH_SIZE];
+            n += 1;
+            painter.paint_and_update_textures(
+                surface.canvas(),
+                1.0,
+                primitives.clone(),
+                delta(ImageDelta::partial(pos, patch.clone(), options)),
+            )
+        })
+    });
+    group.finish();
+}
+
+fn rasterize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rasterize_ui");
+    group.sample_size(10);
+    for scene in Scene::ALL {
+        for size in SIZES {
+            for pixels_per_point in PIXELS_PER_POINT {
+                let id = format!("{}/{}x{}", scene.name(), size.0, size.1);
+                group.bench_with_input(
+                    BenchmarkId::new(id, pixels_per_point),
+                    &pixels_per_point,
+                    |b, &pixels_per_point| {
+                        b.iter_batched(
+                            || scene.ui(),
+                            |ui| {
+                                rasterize_ui(
+                                    size,
+                                    ui,
+                                    Some(RasterizeOptions {
+                                        pixels_per_point,
+                                        ..Default::default()
+                                    }),
+                                )
+                            },
+                            BatchSize::LargeInput,
+                        )
+                    },
+                );
+            }
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    run_ui,
+    tessellate,
+    paint_meshes,
+    texture_upload,
+    rasterize
+);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+//! Renders a small form into a tagged PDF, output.pdf, with the widgets in its structure
+//! element tree, their text as alt text and their content marked, and prints the
+//! accessibility outline of the form.
+
+use skia_safe::pdf::{self, Metadata};
+use smol_egui_skia::EguiSkia;
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let size = (400, 200);
+    let mut backend = EguiSkia::new(1.0);
+    backend.egui_ctx.enable_accesskit();
+
+    let input = egui::RawInput {
+        screen_rect: Some(
+            [
+                egui::Pos2::default(),
+                egui::Pos2::new(size.0 as f32, size.1 as f32),
+            ]
+            .into(),
+        ),
+        ..Default::default()
+    };
+
+    let mut dark_mode = true;
+    let mut volume = 0.5;
+    for _ in 0..2 {
+        backend.run_ui(input.clone(), |ui| {
+            ui.heading("Settings");
+            ui.checkbox(&mut dark_mode, "Dark mode");
+            ui.add(egui::Slider::new(&mut volume, 0.0..=1.0).text("Volume"));
+            let _ = ui.button("Save");
+        });
+    }
+
+    let tree = backend.accessibility_tree().expect("accesskit is enabled");
+    let outline = tree.outline();
+    assert!(outline.contains("CheckBox \"Dark mode\" (checked)"));
+    print!("{outline}");
+
+    let metadata = Metadata {
+        title: "Settings".to_string(),
+        structure_element_tree_root: Some(tree.pdf_structure()),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    let mut document = pdf::new_document(&mut output, Some(&metadata)).begin_page(size, None);
+    backend.paint(document.canvas());
+    document.end_page().close();
+
+    File::create("output.pdf")
+        .unwrap()
+        .write_all(&output)
+        .unwrap();
+
+    println!("wrote output.pdf");
+}
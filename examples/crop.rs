@@ -0,0 +1,34 @@
+//! Renders only the top left corner of a ui at 2x and writes it to output.png.
+
+use egui::{Pos2, Rect, vec2};
+use skia_safe::EncodedImageFormat;
+use smol_egui_skia::{RasterizeOptions, rasterize_ui};
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let mut demo = egui_demo_lib::DemoWindows::default();
+
+    let mut surface = rasterize_ui(
+        (1024, 756),
+        |ui| demo.ui(ui),
+        Some(RasterizeOptions {
+            pixels_per_point: 2.0,
+            crop: Some(Rect::from_min_size(Pos2::new(0.0, 0.0), vec2(200.0, 100.0))),
+            ..Default::default()
+        }),
+    );
+    assert_eq!((surface.width(), surface.height()), (400, 200));
+
+    let data = surface
+        .image_snapshot()
+        .encode(None, EncodedImageFormat::PNG, 100)
+        .expect("Failed to encode image");
+
+    File::create("output.png")
+        .unwrap()
+        .write_all(&data)
+        .unwrap();
+
+    println!("wrote output.png");
+}
@@ -0,0 +1,65 @@
+//! Renders a few links and buttons, writes the image to output.png and the geometry of
+//! the widgets to output.json, and an HTML image map of the links to output.html.
+
+use skia_safe::EncodedImageFormat;
+use smol_egui_skia::{rasterize_ui_with_geometry, tag_link};
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let links = [
+        ("egui", "https://github.com/emilk/egui"),
+        ("skia-safe", "https://github.com/rust-skia/rust-skia"),
+    ];
+
+    let (mut surface, geometry) = rasterize_ui_with_geometry(
+        (300, 200),
+        |ui| {
+            ui.heading("Links");
+            for (label, url) in links {
+                tag_link(&ui.hyperlink_to(label, url), url);
+            }
+            let _ = ui.button("A button");
+        },
+        None,
+    );
+
+    let tagged: Vec<_> = geometry
+        .iter()
+        .filter(|widget| widget.url.is_some())
+        .collect();
+    assert_eq!(tagged.len(), links.len());
+    assert!(geometry.iter().any(|widget| widget.kind == "Button"));
+
+    let data = surface
+        .image_snapshot()
+        .encode(None, EncodedImageFormat::PNG, 100)
+        .expect("Failed to encode image");
+    File::create("output.png")
+        .unwrap()
+        .write_all(&data)
+        .unwrap();
+
+    serde_json::to_writer_pretty(File::create("output.json").unwrap(), &geometry).unwrap();
+
+    let mut html =
+        String::from("<img src=\"output.png\" usemap=\"#links\">\n<map name=\"links\">\n");
+    for widget in tagged {
+        html += &format!(
+            "  <area shape=\"rect\" coords=\"{},{},{},{}\" href=\"{}\" alt=\"{}\">\n",
+            widget.rect.min.x.round(),
+            widget.rect.min.y.round(),
+            widget.rect.max.x.round(),
+            widget.rect.max.y.round(),
+            widget.url.as_deref().unwrap_or_default(),
+            widget.label.as_deref().unwrap_or_default(),
+        );
+    }
+    html += "</map>\n";
+    File::create("output.html")
+        .unwrap()
+        .write_all(html.as_bytes())
+        .unwrap();
+
+    println!("wrote output.png, output.json and output.html");
+}
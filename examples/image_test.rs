@@ -28,6 +28,7 @@ pub fn main() {
         Some(RasterizeOptions {
             pixels_per_point: 1.0,
             frames_before_screenshot: 20,
+            ..Default::default()
         }),
     );
 
@@ -0,0 +1,58 @@
+use egui::{TextureFilter, TextureOptions, include_image};
+use skia_safe::EncodedImageFormat;
+use smol_egui_skia::EguiSkia;
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let mut backend = EguiSkia::new(1.0);
+    egui_extras::install_image_loaders(&backend.egui_ctx);
+
+    let size = (240, 120);
+    let mut surface =
+        skia_safe::surfaces::raster_n32_premul(size).expect("Failed to create surface");
+    let input = egui::RawInput {
+        screen_rect: Some(
+            [
+                egui::Pos2::default(),
+                egui::Pos2::new(size.0 as f32, size.1 as f32),
+            ]
+            .into(),
+        ),
+        ..Default::default()
+    };
+
+    // The same photo scaled down to a thumbnail, without and with mipmaps.
+    let ready = backend.paint_when_ready_ui(
+        surface.canvas(),
+        input,
+        |ui| {
+            ui.horizontal(|ui| {
+                for options in [
+                    TextureOptions::LINEAR,
+                    TextureOptions::LINEAR.with_mipmap_mode(Some(TextureFilter::Linear)),
+                ] {
+                    ui.add(
+                        egui::Image::new(include_image!("assets/ferris.jpg"))
+                            .texture_options(options)
+                            .max_width(100.0),
+                    );
+                }
+            });
+        },
+        None,
+    );
+    assert!(ready, "Images did not load in time");
+
+    let data = surface
+        .image_snapshot()
+        .encode(None, EncodedImageFormat::PNG, 100)
+        .expect("Failed to encode image");
+
+    File::create("output.png")
+        .unwrap()
+        .write_all(&data)
+        .unwrap();
+
+    println!("wrote output.png");
+}
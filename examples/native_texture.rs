@@ -0,0 +1,53 @@
+use egui::load::SizedTexture;
+use egui::{Pos2, TextureOptions};
+use skia_safe::surfaces::raster_n32_premul;
+use skia_safe::{Color, EncodedImageFormat, Paint, Point};
+use smol_egui_skia::EguiSkia;
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let mut backend = EguiSkia::new(1.0);
+
+    // Draw something with skia, and show it in egui without a round trip through egui::ColorImage.
+    let mut offscreen = raster_n32_premul((128, 128)).expect("Failed to create surface");
+    offscreen.canvas().clear(Color::from_rgb(30, 30, 60));
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(Color::from_rgb(255, 140, 0));
+    offscreen
+        .canvas()
+        .draw_circle(Point::new(64.0, 64.0), 48.0, &paint);
+    let texture_id = backend
+        .painter
+        .register_native_texture(offscreen.image_snapshot(), TextureOptions::LINEAR);
+
+    let size = (300, 200);
+    let mut surface = raster_n32_premul(size).expect("Failed to create surface");
+    let input = egui::RawInput {
+        screen_rect: Some([Pos2::default(), Pos2::new(size.0 as f32, size.1 as f32)].into()),
+        ..Default::default()
+    };
+
+    backend.paint_when_ready_ui(
+        surface.canvas(),
+        input,
+        |ui| {
+            ui.label("A skia image:");
+            ui.image(SizedTexture::new(texture_id, egui::vec2(128.0, 128.0)));
+        },
+        None,
+    );
+
+    let data = surface
+        .image_snapshot()
+        .encode(None, EncodedImageFormat::PNG, 100)
+        .expect("Failed to encode image");
+
+    File::create("output.png")
+        .unwrap()
+        .write_all(&data)
+        .unwrap();
+
+    println!("wrote output.png");
+}
@@ -0,0 +1,35 @@
+//! Draws a ui with links onto a PDF page and writes it to output.pdf. The links stay
+//! clickable in PDF viewers.
+
+use smol_egui_skia::{RasterizeOptions, draw_onto_canvas_ui, tag_link};
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let mut pdf = Vec::new();
+    let mut document = skia_safe::pdf::new_document(&mut pdf, None).begin_page((400, 200), None);
+
+    draw_onto_canvas_ui(
+        document.canvas(),
+        |ui| {
+            ui.heading("Report");
+            ui.label("Rendered with");
+            for (label, url) in [
+                ("egui", "https://github.com/emilk/egui"),
+                ("skia", "https://skia.org"),
+            ] {
+                tag_link(&ui.hyperlink_to(label, url), url);
+            }
+        },
+        Some(RasterizeOptions {
+            link_annotations: true,
+            ..Default::default()
+        }),
+    );
+
+    document.end_page().close();
+
+    File::create("output.pdf").unwrap().write_all(&pdf).unwrap();
+
+    println!("wrote output.pdf");
+}
@@ -0,0 +1,58 @@
+//! Records a frame of the egui demo into `.skp` bytes once, loads the picture from them,
+//! replays it at its own, half and double size, and writes the results to output_<name>.png
+//! and output.skp.
+
+use skia_safe::{Color, EncodedImageFormat, Picture, Surface, surfaces::raster_n32_premul};
+use smol_egui_skia::EguiSkia;
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let size = (1024, 756);
+    let mut backend = EguiSkia::new(1.0);
+    let input = egui::RawInput {
+        screen_rect: Some(
+            [
+                egui::Pos2::default(),
+                egui::Pos2::new(size.0 as f32, size.1 as f32),
+            ]
+            .into(),
+        ),
+        ..Default::default()
+    };
+
+    let mut demo = egui_demo_lib::DemoWindows::default();
+    for _ in 0..2 {
+        backend.run_ui(input.clone(), |ui| demo.ui(ui));
+    }
+    let skp = backend.paint_to_skp();
+    File::create("output.skp")
+        .unwrap()
+        .write_all(skp.as_bytes())
+        .unwrap();
+    let picture = Picture::from_data(&skp).expect("Failed to load picture");
+    assert_eq!(picture.cull_rect().width() as i32, size.0);
+
+    for (name, mut surface) in [
+        ("original", replay(&picture, size, 1.0)),
+        ("half", replay(&picture, (size.0 / 2, size.1 / 2), 0.5)),
+        ("double", replay(&picture, (size.0 * 2, size.1 * 2), 2.0)),
+    ] {
+        let data = surface
+            .image_snapshot()
+            .encode(None, EncodedImageFormat::PNG, 100)
+            .expect("Failed to encode image");
+        let path = format!("output_{name}.png");
+        File::create(&path).unwrap().write_all(&data).unwrap();
+        println!("wrote {path}");
+    }
+}
+
+fn replay(picture: &Picture, size: (i32, i32), scale: f32) -> Surface {
+    let mut surface = raster_n32_premul(size).expect("Failed to create surface");
+    let canvas = surface.canvas();
+    canvas.clear(Color::TRANSPARENT);
+    canvas.scale((scale, scale));
+    picture.playback(canvas);
+    surface
+}
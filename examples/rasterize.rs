@@ -17,9 +17,16 @@ pub fn main() {
                         ui.allocate_exact_size(egui::Vec2::splat(300.0), egui::Sense::drag());
                     ui.painter().add(egui::PaintCallback {
                         rect,
-                        callback: std::sync::Arc::new(EguiSkiaPaintCallback::new(move |canvas| {
-                            canvas.draw_circle(Point::new(150.0, 150.0), 150.0, &Paint::default());
-                        })),
+                        callback: std::sync::Arc::new(EguiSkiaPaintCallback::new_with_info(
+                            move |canvas, info| {
+                                let size = info.viewport.size();
+                                canvas.draw_circle(
+                                    Point::new(size.x / 2.0, size.y / 2.0),
+                                    size.min_elem() / 2.0,
+                                    &Paint::default(),
+                                );
+                            },
+                        )),
                     })
                 });
             });
@@ -27,6 +34,7 @@ pub fn main() {
         Some(RasterizeOptions {
             pixels_per_point: 1.0,
             frames_before_screenshot: 2,
+            ..Default::default()
         }),
     );
 
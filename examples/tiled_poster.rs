@@ -0,0 +1,40 @@
+//! Renders a large image in horizontal strips and streams them into a PNG, so only one
+//! strip is held in memory at a time.
+
+use skia_safe::{AlphaType, ColorType, ImageInfo};
+use smol_egui_skia::rasterize_ui_tiled;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+pub fn main() {
+    let size = (6000, 4000);
+    let strip_height = 256;
+
+    let mut demo = egui_demo_lib::DemoWindows::default();
+
+    let file = File::create("output.png").unwrap();
+    let mut encoder = png::Encoder::new(BufWriter::new(file), size.0 as u32, size.1 as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    let mut stream = writer.stream_writer().unwrap();
+
+    let mut pixels = Vec::new();
+    rasterize_ui_tiled(
+        size,
+        (size.0, strip_height),
+        |ui| demo.ui(ui),
+        None,
+        |tile, surface| {
+            let info = ImageInfo::new(tile.size(), ColorType::RGBA8888, AlphaType::Unpremul, None);
+            let row_bytes = tile.width() as usize * 4;
+            pixels.resize(row_bytes * tile.height() as usize, 0);
+            assert!(surface.read_pixels(&info, &mut pixels, row_bytes, (0, 0)));
+            stream.write_all(&pixels).unwrap();
+        },
+    );
+
+    stream.finish().unwrap();
+
+    println!("wrote output.png");
+}
@@ -0,0 +1,55 @@
+//! Shows an immediate viewport next to the root viewport and writes each of them to
+//! output_<viewport>.png.
+
+use egui::{ViewportBuilder, ViewportId};
+use skia_safe::EncodedImageFormat;
+use smol_egui_skia::{RasterizeOptions, rasterize_viewports};
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let inspector = ViewportId::from_hash_of("inspector");
+
+    let surfaces = rasterize_viewports(
+        (400, 300),
+        |ui| {
+            ui.heading("Root viewport");
+            ui.ctx().show_viewport_immediate(
+                inspector,
+                ViewportBuilder::default()
+                    .with_title("Inspector")
+                    .with_inner_size([200.0, 100.0]),
+                |ui, _class| {
+                    ui.heading("Inspector");
+                    ui.label("Rendered into its own image");
+                },
+            );
+        },
+        Some(RasterizeOptions {
+            pixels_per_point: 2.0,
+            ..Default::default()
+        }),
+    );
+
+    assert_eq!(surfaces.len(), 2);
+    let inspector_surface = &surfaces[&inspector];
+    assert_eq!(
+        (inspector_surface.width(), inspector_surface.height()),
+        (400, 200)
+    );
+
+    for (id, mut surface) in surfaces {
+        let name = if id == ViewportId::ROOT {
+            "root"
+        } else {
+            "inspector"
+        };
+        let data = surface
+            .image_snapshot()
+            .encode(None, EncodedImageFormat::PNG, 100)
+            .expect("Failed to encode image");
+        let path = format!("output_{name}.png");
+        File::create(&path).unwrap().write_all(&data).unwrap();
+        println!("wrote {path}");
+    }
+}
@@ -0,0 +1,43 @@
+//! Lays out a small form once and writes an image of each tagged widget to
+//! widget_<name>.png.
+
+use egui::Id;
+use skia_safe::EncodedImageFormat;
+use smol_egui_skia::{RasterizeOptions, rasterize_widgets, tag_widget};
+use std::fs::File;
+use std::io::Write;
+
+pub fn main() {
+    let names = ["label", "checkbox", "button", "slider"];
+    let ids: Vec<Id> = names.iter().map(Id::new).collect();
+
+    let mut checked = true;
+    let mut value = 0.5;
+    let surfaces = rasterize_widgets(
+        (400, 300),
+        |ui| {
+            tag_widget(&ui.label("Widget screenshots"), ids[0]);
+            tag_widget(&ui.checkbox(&mut checked, "Checked"), ids[1]);
+            tag_widget(&ui.button("Click me"), ids[2]);
+            tag_widget(&ui.add(egui::Slider::new(&mut value, 0.0..=1.0)), ids[3]);
+        },
+        &ids,
+        4.0,
+        Some(RasterizeOptions {
+            pixels_per_point: 2.0,
+            ..Default::default()
+        }),
+    );
+    assert_eq!(surfaces.len(), names.len());
+
+    for (name, (_, mut surface)) in names.iter().zip(surfaces) {
+        let data = surface
+            .image_snapshot()
+            .encode(None, EncodedImageFormat::PNG, 100)
+            .expect("Failed to encode image");
+
+        let path = format!("widget_{name}.png");
+        File::create(&path).unwrap().write_all(&data).unwrap();
+        println!("wrote {path}");
+    }
+}
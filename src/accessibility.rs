@@ -0,0 +1,209 @@
+use std::fmt::Write;
+
+use ahash::AHashMap;
+use egui::accesskit::{Node, NodeId, Role, Toggled, TreeUpdate};
+use skia_safe::pdf::StructureElementNode;
+
+use crate::geometry::{node_rect, widget_kind};
+
+/// The accessibility tree egui reported for a frame through AccessKit, e.g. to provide
+/// alt text and structure for rendered images. See [`crate::EguiSkia::accessibility_tree`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccessibilityTree {
+    update: TreeUpdate,
+}
+
+impl AccessibilityTree {
+    pub(crate) fn new(update: TreeUpdate) -> Self {
+        Self { update }
+    }
+
+    /// The full AccessKit tree, with bounds in points.
+    pub fn tree_update(&self) -> &TreeUpdate {
+        &self.update
+    }
+
+    /// The rect of the widget with `id`, in points.
+    pub(crate) fn widget_rect(&self, id: egui::Id) -> Option<egui::Rect> {
+        let node_id = id.accesskit_id();
+        self.update
+            .nodes
+            .iter()
+            .find(|(id, _)| *id == node_id)
+            .and_then(|(_, node)| node_rect(node))
+    }
+
+    /// A text outline of the tree with one widget per line, indented by nesting, e.g.
+    ///
+    /// ```text
+    /// Label "Settings"
+    /// CheckBox "Dark mode" (checked)
+    /// Slider "Volume" = 0.5
+    /// Link "egui" -> https://github.com/emilk/egui
+    /// ```
+    ///
+    /// Widgets are named like [`crate::WidgetGeometry::kind`]. Layout containers are left out.
+    pub fn outline(&self) -> String {
+        let mut outline = String::new();
+        self.visit(&mut |node, depth| {
+            let _ = write!(
+                outline,
+                "{:indent$}{}",
+                "",
+                widget_kind(node.role()),
+                indent = depth * 2
+            );
+            if let Some(label) = label(node) {
+                let _ = write!(outline, " {label:?}");
+            }
+            if let (Some(_), Some(value)) = (node.label(), node.value()) {
+                let _ = write!(outline, " = {value:?}");
+            } else if let Some(value) = node.numeric_value() {
+                let _ = write!(outline, " = {value}");
+            }
+            match node.toggled() {
+                Some(Toggled::True) => outline.push_str(" (checked)"),
+                Some(Toggled::Mixed) => outline.push_str(" (mixed)"),
+                Some(Toggled::False) | None => {}
+            }
+            if node.is_disabled() {
+                outline.push_str(" (disabled)");
+            }
+            if let Some(url) = node.url() {
+                let _ = write!(outline, " -> {url}");
+            }
+            outline.push('\n');
+        });
+        outline
+    }
+
+    /// The tree as the structure element tree of a PDF, to pass as
+    /// [`skia_safe::pdf::Metadata::structure_element_tree_root`].
+    ///
+    /// Every widget becomes an element with its text as alt text. While accesskit is enabled,
+    /// [`crate::EguiSkia::paint`] marks the content it draws with the id of the element of the
+    /// widget it belongs to, so paint the same frame onto the page of the document to get a
+    /// tagged PDF.
+    pub fn pdf_structure(&self) -> StructureElementNode<'static> {
+        let mut document = StructureElementNode::new("Document");
+        document.set_node_id(DOCUMENT_NODE_ID);
+
+        // Elements are built bottom up, as children are moved into their parents.
+        let mut stack = Vec::new();
+        self.visit_elements(&mut |node, depth, node_id| {
+            finish(&mut stack, &mut document, depth);
+
+            let mut element = StructureElementNode::new(pdf_type(node.role()));
+            element.set_node_id(node_id);
+            if let Some(label) = label(node) {
+                element.set_alt(label);
+            }
+            stack.push((depth, element));
+        });
+        finish(&mut stack, &mut document, 0);
+
+        document
+    }
+
+    /// The ids of the elements of [`Self::pdf_structure`], with the rects of their widgets
+    /// in points.
+    pub(crate) fn pdf_node_rects(&self) -> Vec<(i32, egui::Rect)> {
+        let mut rects = Vec::new();
+        self.visit_elements(&mut |node, _, node_id| {
+            if let Some(rect) = node_rect(node) {
+                rects.push((node_id, rect));
+            }
+        });
+        rects
+    }
+
+    /// Like [`Self::visit`], but also passes the id of the PDF structure element of every node.
+    fn visit_elements(&self, visit: &mut impl FnMut(&Node, usize, i32)) {
+        let mut node_id = DOCUMENT_NODE_ID;
+        self.visit(&mut |node, depth| {
+            node_id += 1;
+            visit(node, depth, node_id);
+        });
+    }
+
+    /// Calls `visit` with every node below the root and its depth, depth first,
+    /// skipping layout containers.
+    fn visit(&self, visit: &mut impl FnMut(&Node, usize)) {
+        let nodes: AHashMap<NodeId, &Node> = self
+            .update
+            .nodes
+            .iter()
+            .map(|(id, node)| (*id, node))
+            .collect();
+        let Some(root) = self
+            .update
+            .tree
+            .as_ref()
+            .and_then(|tree| nodes.get(&tree.root))
+        else {
+            return;
+        };
+
+        fn visit_children(
+            nodes: &AHashMap<NodeId, &Node>,
+            node: &Node,
+            depth: usize,
+            visit: &mut impl FnMut(&Node, usize),
+        ) {
+            for child in node.children().iter().filter_map(|id| nodes.get(id)) {
+                if matches!(child.role(), Role::GenericContainer | Role::TextRun) {
+                    visit_children(nodes, child, depth, visit);
+                } else {
+                    visit(child, depth);
+                    visit_children(nodes, child, depth + 1, visit);
+                }
+            }
+        }
+        visit_children(&nodes, root, 0, visit);
+    }
+}
+
+/// The id of the root element of [`AccessibilityTree::pdf_structure`]. Ids of 0 and below
+/// are reserved by skia.
+const DOCUMENT_NODE_ID: i32 = 1;
+
+/// Moves the elements on `stack` at `depth` or deeper into their parents.
+fn finish(
+    stack: &mut Vec<(usize, StructureElementNode<'static>)>,
+    document: &mut StructureElementNode<'static>,
+    depth: usize,
+) {
+    while stack.last().is_some_and(|(d, _)| *d >= depth) {
+        let (_, element) = stack.pop().unwrap();
+        match stack.last_mut() {
+            Some((_, parent)) => parent.append_child(element),
+            None => document.append_child(element),
+        };
+    }
+}
+
+/// egui puts the text of labels into their value.
+fn label(node: &Node) -> Option<&str> {
+    node.label().or(node.value())
+}
+
+/// The standard PDF structure type for `role`.
+fn pdf_type(role: Role) -> &'static str {
+    match role {
+        Role::Label => "P",
+        Role::Link => "Link",
+        Role::Image => "Figure",
+        Role::Button
+        | Role::CheckBox
+        | Role::RadioButton
+        | Role::ComboBox
+        | Role::Slider
+        | Role::SpinButton
+        | Role::ColorWell
+        | Role::TextInput
+        | Role::MultilineTextInput
+        | Role::PasswordInput => "Form",
+        _ => "Div",
+    }
+}
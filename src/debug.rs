@@ -0,0 +1,157 @@
+use egui::TextureId;
+use egui::epaint::Mesh16;
+use skia_safe::{
+    Canvas, Color, Font, FontMgr, FontStyle, Paint, PaintStyle, Path, PathBuilder, Point, Rect,
+};
+
+/// What the debug overlay of [`crate::Painter`] should draw on top of the painted output.
+///
+/// Everything is colour-coded: clip rects are red, mesh wireframes green,
+/// callback rects blue and texture ids yellow.
+#[derive(Clone, Debug, Default)]
+pub struct DebugOptions {
+    /// Outline the `clip_rect` of every primitive.
+    pub clip_rects: bool,
+    /// Draw the triangles of every mesh.
+    pub mesh_wireframes: bool,
+    /// Outline the rect of every paint callback.
+    pub callback_rects: bool,
+    /// Label every mesh with the id of its texture.
+    pub texture_ids: bool,
+    /// Draw the overlay into a separate image, see [`crate::Painter::take_debug_layer`],
+    /// instead of onto the painted canvas.
+    pub separate_layer: bool,
+}
+
+impl DebugOptions {
+    /// Everything enabled, drawn onto the painted canvas.
+    pub fn all() -> Self {
+        Self {
+            clip_rects: true,
+            mesh_wireframes: true,
+            callback_rects: true,
+            texture_ids: true,
+            separate_layer: false,
+        }
+    }
+}
+
+const CLIP_RECT_COLOR: Color = Color::from_argb(200, 255, 0, 0);
+const WIREFRAME_COLOR: Color = Color::from_argb(120, 0, 200, 0);
+const CALLBACK_RECT_COLOR: Color = Color::from_argb(200, 0, 80, 255);
+const TEXTURE_ID_COLOR: Color = Color::from_argb(255, 230, 180, 0);
+
+/// Geometry of one painted primitive, in points.
+pub(crate) enum DebugPrimitive {
+    Mesh {
+        clip_rect: Rect,
+        bounds: Rect,
+        texture_id: TextureId,
+        wireframe: Path,
+    },
+    Callback {
+        clip_rect: Rect,
+        rect: Rect,
+    },
+}
+
+impl DebugPrimitive {
+    pub(crate) fn mesh(clip_rect: Rect, mesh: &Mesh16) -> Self {
+        let mut wireframe = PathBuilder::new();
+        for triangle in mesh.indices.chunks_exact(3) {
+            let points: Vec<Point> = triangle
+                .iter()
+                .map(|&index| {
+                    let pos = mesh.vertices[index as usize].pos;
+                    Point::new(pos.x, pos.y)
+                })
+                .collect();
+            wireframe.add_polygon(&points, true);
+        }
+        let wireframe = wireframe.detach();
+
+        Self::Mesh {
+            clip_rect,
+            bounds: *wireframe.bounds(),
+            texture_id: mesh.texture_id,
+            wireframe,
+        }
+    }
+
+    fn clip_rect(&self) -> Rect {
+        match self {
+            DebugPrimitive::Mesh { clip_rect, .. } | DebugPrimitive::Callback { clip_rect, .. } => {
+                *clip_rect
+            }
+        }
+    }
+}
+
+/// Draws the overlay for `primitives` onto `canvas`, scaled by `dpi`.
+pub(crate) fn draw_overlay(
+    canvas: &Canvas,
+    dpi: f32,
+    options: &DebugOptions,
+    primitives: &[DebugPrimitive],
+) {
+    let arc = skia_safe::AutoCanvasRestore::guard(canvas, true);
+    arc.scale((dpi, dpi));
+
+    // A stroke width of 0 draws hairlines, which stay one pixel wide at any scale.
+    let stroke = |color: Color| {
+        let mut paint = Paint::default();
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(0.0);
+        paint.set_anti_alias(true);
+        paint.set_color(color);
+        paint
+    };
+
+    if options.mesh_wireframes {
+        let paint = stroke(WIREFRAME_COLOR);
+        for primitive in primitives {
+            if let DebugPrimitive::Mesh { wireframe, .. } = primitive {
+                arc.draw_path(wireframe, &paint);
+            }
+        }
+    }
+
+    if options.clip_rects {
+        let paint = stroke(CLIP_RECT_COLOR);
+        for primitive in primitives {
+            arc.draw_rect(primitive.clip_rect(), &paint);
+        }
+    }
+
+    if options.callback_rects {
+        let paint = stroke(CALLBACK_RECT_COLOR);
+        for primitive in primitives {
+            if let DebugPrimitive::Callback { rect, .. } = primitive {
+                arc.draw_rect(rect, &paint);
+            }
+        }
+    }
+
+    if options.texture_ids {
+        let Some(typeface) = FontMgr::new().legacy_make_typeface(None, FontStyle::default()) else {
+            return;
+        };
+        let font = Font::new(typeface, 8.0);
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(TEXTURE_ID_COLOR);
+
+        for primitive in primitives {
+            if let DebugPrimitive::Mesh {
+                bounds, texture_id, ..
+            } = primitive
+            {
+                let label = match texture_id {
+                    TextureId::Managed(id) => format!("M{id}"),
+                    TextureId::User(id) => format!("U{id}"),
+                };
+                arc.draw_str(label, (bounds.left, bounds.top + 8.0), &font, &paint);
+            }
+        }
+    }
+}
@@ -1,13 +1,36 @@
-use egui::{Context, Pos2, Ui};
-use skia_safe::{Canvas, Surface, surface::surfaces::raster_n32_premul};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
-use crate::painter::Painter;
+use ahash::AHashSet;
+use egui::epaint::{ClippedShape, Primitive};
+use egui::load::{SizedTexture, TextureLoader as _};
+use egui::{ClippedPrimitive, Context, Pos2, TextureId, TextureOptions, Ui};
+use skia_safe::{
+    Canvas, IRect, Picture, PictureRecorder, Surface, surface::surfaces::raster_n32_premul,
+};
+
+use crate::accessibility::AccessibilityTree;
+use crate::geometry::{WidgetGeometry, widget_geometry};
+use crate::painter::{Painter, RenderStats, visible_rect};
+use crate::texture_loader::TrackingTextureLoader;
+use crate::viewports::{
+    ViewportFrame, render_deferred_viewports, render_immediate_viewports, take_frames,
+};
 
 pub struct RasterizeOptions {
+    /// The number of pixels per point. Sizes are in pixels, so the ui is laid out on a
+    /// screen of `size / pixels_per_point` points.
     pub pixels_per_point: f32,
     /// The number of frames to render before a screenshot is taken.
     /// Default is 2, so egui will be able to display windows
     pub frames_before_screenshot: usize,
+    /// Only paint this part of the ui, in points. The surface returned by [`rasterize_ui`]
+    /// is sized to the crop, and [`draw_onto_canvas_ui`] paints the crop at the canvas origin.
+    /// Ignored by [`rasterize_ui_tiled`].
+    pub crop: Option<egui::Rect>,
+    /// Cover links tagged with [`crate::tag_link`] with link annotations, so they stay
+    /// clickable when drawing onto the canvas of a skia PDF document. Enables accesskit.
+    pub link_annotations: bool,
 }
 
 impl Default for RasterizeOptions {
@@ -15,6 +38,8 @@ impl Default for RasterizeOptions {
         Self {
             pixels_per_point: 1.0,
             frames_before_screenshot: 2,
+            crop: None,
+            link_annotations: false,
         }
     }
 }
@@ -33,9 +58,63 @@ pub fn rasterize_ui(
     ui: impl FnMut(&mut Ui),
     options: Option<RasterizeOptions>,
 ) -> Surface {
-    let mut surface = raster_n32_premul(size).expect("Failed to create surface");
-    draw_onto_canvas_ui(surface.canvas(), ui, options);
-    surface
+    rasterize_ui_inner(size, ui, options, false).0
+}
+
+/// Like [`rasterize_ui`], but also returns where each widget ended up in the surface,
+/// e.g. to build an image map of its links and buttons. See [`EguiSkia::widget_geometry`].
+pub fn rasterize_ui_with_geometry(
+    size: (i32, i32),
+    ui: impl FnMut(&mut Ui),
+    options: Option<RasterizeOptions>,
+) -> (Surface, Vec<WidgetGeometry>) {
+    let (surface, geometry, _) = rasterize_ui_inner(size, ui, options, true);
+    (surface, geometry)
+}
+
+/// Like [`rasterize_ui`], but also returns the accessibility tree of the ui, e.g. to provide
+/// alt text for the image. See [`EguiSkia::accessibility_tree`].
+///
+/// The tree is `None` if no frame was run.
+pub fn rasterize_ui_with_accessibility(
+    size: (i32, i32),
+    ui: impl FnMut(&mut Ui),
+    options: Option<RasterizeOptions>,
+) -> (Surface, Option<AccessibilityTree>) {
+    let (surface, _, accessibility_tree) = rasterize_ui_inner(size, ui, options, true);
+    (surface, accessibility_tree)
+}
+
+fn rasterize_ui_inner(
+    size: (i32, i32),
+    ui: impl FnMut(&mut Ui),
+    options: Option<RasterizeOptions>,
+    accesskit: bool,
+) -> (Surface, Vec<WidgetGeometry>, Option<AccessibilityTree>) {
+    let options = options.unwrap_or_default();
+    let surface_size = match options.crop {
+        Some(crop) => pixel_size(crop, options.pixels_per_point),
+        None => size,
+    };
+    let mut surface = raster_n32_premul(surface_size).expect("Failed to create surface");
+    let (geometry, accessibility_tree) = draw_ui(surface.canvas(), size, ui, options, accesskit);
+    (surface, geometry, accessibility_tree)
+}
+
+/// Like [`rasterize_ui`], but lays out the ui once and then paints it tile by tile, calling
+/// `on_tile` with the rect of every tile in the full image and a surface holding its pixels.
+///
+/// Only one tile is held in memory at a time, so this can produce images that are too large
+/// for a single surface.
+pub fn rasterize_ui_tiled(
+    size: (i32, i32),
+    tile_size: (i32, i32),
+    ui: impl FnMut(&mut Ui),
+    options: Option<RasterizeOptions>,
+    on_tile: impl FnMut(IRect, &mut Surface),
+) {
+    let mut backend = run_frames(size, ui, &options.unwrap_or_default(), |_| {});
+    backend.paint_tiled(size, tile_size, on_tile);
 }
 
 pub fn draw_onto_canvas(
@@ -48,32 +127,187 @@ pub fn draw_onto_canvas(
 
 pub fn draw_onto_canvas_ui(
     canvas: &Canvas,
-    mut ui: impl FnMut(&mut Ui),
+    ui: impl FnMut(&mut Ui),
     options: Option<RasterizeOptions>,
 ) {
+    let image_info = canvas.image_info();
+    draw_ui(
+        canvas,
+        (image_info.width(), image_info.height()),
+        ui,
+        options.unwrap_or_default(),
+        false,
+    );
+}
+
+/// Renders `ui` on a screen of `size` pixels and returns one surface for the root viewport
+/// and one for each immediate or deferred viewport the ui shows. See
+/// [`EguiSkia::paint_viewports`].
+///
+/// The crop of `options` is ignored.
+pub fn rasterize_viewports(
+    size: (i32, i32),
+    ui: impl FnMut(&mut Ui),
+    options: Option<RasterizeOptions>,
+) -> egui::OrderedViewportIdMap<Surface> {
+    let mut backend = run_frames(
+        size,
+        ui,
+        &options.unwrap_or_default(),
+        EguiSkia::enable_immediate_viewports,
+    );
+    backend.paint_viewports()
+}
+
+/// Renders `ui` on a screen of `size` pixels and returns one surface for each widget with
+/// one of `ids`, grown by `padding` points on every side. See [`EguiSkia::paint_widgets`].
+///
+/// The crop of `options` is ignored.
+pub fn rasterize_widgets(
+    size: (i32, i32),
+    ui: impl FnMut(&mut Ui),
+    ids: &[egui::Id],
+    padding: f32,
+    options: Option<RasterizeOptions>,
+) -> Vec<(egui::Id, Surface)> {
+    let mut backend = run_frames(size, ui, &options.unwrap_or_default(), |backend| {
+        backend.egui_ctx.enable_accesskit();
+    });
+    backend.paint_widgets(ids, padding)
+}
+
+/// Tag the widget of `response` with `id`, so [`EguiSkia::widget_rect`] and
+/// [`rasterize_widgets`] find it by `id`. Useful for widgets whose own id is hard to know.
+pub fn tag_widget(response: &egui::Response, id: egui::Id) {
+    response
+        .ctx
+        .data_mut(|data| data.insert_temp(tag_id(id), response.rect));
+}
+
+fn tag_id(id: egui::Id) -> egui::Id {
+    id.with("smol_egui_skia_widget_tag")
+}
+
+/// Lays out `ui` on a screen of `size` pixels and paints it onto `canvas`.
+///
+/// If `accesskit` is set, returns the geometry of the widgets relative to `canvas` and the
+/// accessibility tree of the last frame.
+fn draw_ui(
+    canvas: &Canvas,
+    size: (i32, i32),
+    ui: impl FnMut(&mut Ui),
+    options: RasterizeOptions,
+    accesskit: bool,
+) -> (Vec<WidgetGeometry>, Option<AccessibilityTree>) {
+    let mut backend = run_frames(size, ui, &options, |backend| {
+        if accesskit || options.link_annotations {
+            backend.egui_ctx.enable_accesskit();
+        }
+    });
     let RasterizeOptions {
         pixels_per_point,
-        frames_before_screenshot,
-    } = options.unwrap_or_default();
-    let mut backend = EguiSkia::new(pixels_per_point);
+        crop,
+        ..
+    } = options;
 
-    let image_info = canvas.image_info();
+    let accessibility_tree = backend.accessibility_tree().filter(|_| accesskit).cloned();
+    let mut geometry = accessibility_tree
+        .as_ref()
+        .map(|tree| widget_geometry(tree.tree_update(), pixels_per_point))
+        .unwrap_or_default();
 
-    let input = egui::RawInput {
-        screen_rect: Some(
-            [
-                Pos2::default(),
-                Pos2::new(image_info.width() as f32, image_info.height() as f32),
-            ]
-            .into(),
-        ),
-        ..Default::default()
-    };
+    match crop {
+        Some(crop) => {
+            let offset = crop.min.to_vec2() * pixels_per_point;
+            for widget in &mut geometry {
+                widget.rect = widget.rect.translate(-offset);
+            }
+            backend.paint_crop(canvas, crop);
+        }
+        None => backend.paint(canvas),
+    }
+    (geometry, accessibility_tree)
+}
+
+/// Pairs every primitive with its [`visible_rect`]. Most primitives share the clip rect of
+/// their panel, so culling by the clip rect alone keeps almost all of them.
+fn with_visible_rects(primitives: Vec<ClippedPrimitive>) -> Vec<(egui::Rect, ClippedPrimitive)> {
+    primitives
+        .into_iter()
+        .map(|primitive| (visible_rect(&primitive), primitive))
+        .collect()
+}
 
-    for _ in 0..frames_before_screenshot {
+/// The primitives of `primitives` that draw into `area`, see [`with_visible_rects`].
+fn cull(primitives: &[(egui::Rect, ClippedPrimitive)], area: egui::Rect) -> Vec<ClippedPrimitive> {
+    primitives
+        .iter()
+        .filter(|(visible_rect, _)| !visible_rect.intersect(area).is_negative())
+        .map(|(_, primitive)| primitive.clone())
+        .collect()
+}
+
+/// Creates a backend for `options` and runs `ui` on a screen of `size` pixels for
+/// [`RasterizeOptions::frames_before_screenshot`] frames. `setup` is called on the backend
+/// before the first frame.
+fn run_frames(
+    size: (i32, i32),
+    mut ui: impl FnMut(&mut Ui),
+    options: &RasterizeOptions,
+    setup: impl FnOnce(&EguiSkia),
+) -> EguiSkia {
+    let mut backend = EguiSkia::new(options.pixels_per_point);
+    setup(&backend);
+
+    let input = screen_input(size.0, size.1, options.pixels_per_point);
+
+    for _ in 0..options.frames_before_screenshot {
         backend.run_ui(input.clone(), &mut ui);
     }
-    backend.paint(canvas);
+    backend
+}
+
+/// The size in pixels of `rect`, which is in points.
+fn pixel_size(rect: egui::Rect, pixels_per_point: f32) -> (i32, i32) {
+    (
+        ((rect.width() * pixels_per_point).round() as i32).max(1),
+        ((rect.height() * pixels_per_point).round() as i32).max(1),
+    )
+}
+
+/// Input for a screen of `width` by `height` pixels. The screen rect is in points.
+fn screen_input(width: i32, height: i32, pixels_per_point: f32) -> egui::RawInput {
+    egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            Pos2::default(),
+            egui::vec2(width as f32, height as f32) / pixels_per_point,
+        )),
+        ..Default::default()
+    }
+}
+
+/// The output of a frame run with [`EguiSkia::run_full`] or [`EguiSkia::run_ui_full`].
+///
+/// This is [`egui::FullOutput`] without the shapes and texture changes, which [`EguiSkia`]
+/// keeps until the frame is painted.
+#[derive(Clone, Default)]
+pub struct RunOutput {
+    /// Non-rendering related output, like cursor icons, copied text and opened urls.
+    pub platform_output: egui::PlatformOutput,
+    /// The number of pixels per point egui laid the frame out with.
+    pub pixels_per_point: f32,
+    /// All active viewports, including the root, with their repaint delays and commands.
+    pub viewport_output: egui::OrderedViewportIdMap<egui::ViewportOutput>,
+}
+
+impl RunOutput {
+    /// How long until egui wants the root viewport to be repainted.
+    /// [`std::time::Duration::MAX`] if it does not need a repaint.
+    pub fn repaint_delay(&self) -> Duration {
+        self.viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map_or(Duration::MAX, |output| output.repaint_delay)
+    }
 }
 
 /// Convenience wrapper for using [`egui`] from a [`skia`] app.
@@ -81,25 +315,140 @@ pub struct EguiSkia {
     pub egui_ctx: Context,
     pub painter: Painter,
 
+    /// The scale factor set by the user, passed to egui with every frame.
     pixels_per_point: f32,
 
-    shapes: Vec<egui::epaint::ClippedShape>,
+    shapes: Vec<ClippedShape>,
+    /// The pixels per point egui laid out `shapes` with, which they are tessellated and
+    /// painted at.
+    shapes_pixels_per_point: f32,
+    /// Tessellation of `shapes`, computed at most once per frame and shared
+    /// between [`EguiSkia::are_textures_loaded`] and [`EguiSkia::paint`].
+    tessellated: OnceLock<TessellatedFrame>,
     textures_delta: egui::TexturesDelta,
+    /// Rects, in points, and urls of the links tagged with [`crate::tag_link`] in the last frame.
+    links: Vec<(egui::Rect, String)>,
+    accessibility_tree: Option<AccessibilityTree>,
+    /// The elements of the PDF structure of `accessibility_tree` with the rects of their
+    /// widgets in points, see [`Painter::paint_tagged`].
+    pdf_nodes: Vec<(i32, egui::Rect)>,
+    /// The immediate viewports shown in the last frame.
+    viewports: Vec<ViewportFrame>,
+    /// Loads the images of `egui_ctx` into textures, and knows which ones can be evicted.
+    texture_loader: Arc<TrackingTextureLoader>,
+}
+
+struct TessellatedFrame {
+    clipped_primitives: Vec<ClippedPrimitive>,
+    texture_ids: AHashSet<TextureId>,
+    pixels_per_point: f32,
+    tessellation_time: Duration,
+}
+
+impl TessellatedFrame {
+    fn new(egui_ctx: &Context, shapes: Vec<ClippedShape>, pixels_per_point: f32) -> Self {
+        profile_scope!("tessellate");
+        let start = Instant::now();
+        let clipped_primitives = egui_ctx.tessellate(shapes, pixels_per_point);
+        let tessellation_time = start.elapsed();
+        let texture_ids = clipped_primitives
+            .iter()
+            .filter_map(|primitive| match &primitive.primitive {
+                Primitive::Mesh(mesh) => Some(mesh.texture_id),
+                Primitive::Callback(_) => None,
+            })
+            .collect();
+
+        Self {
+            clipped_primitives,
+            texture_ids,
+            pixels_per_point,
+            tessellation_time,
+        }
+    }
 }
 
 impl EguiSkia {
     pub fn new(pixels_per_point: f32) -> Self {
         let painter = Painter::new();
+        let egui_ctx = Context::default();
+        let texture_loader = Arc::new(TrackingTextureLoader::default());
+        egui_ctx.add_texture_loader(texture_loader.clone());
         Self {
             pixels_per_point,
-            egui_ctx: Default::default(),
+            egui_ctx,
             painter,
             shapes: Default::default(),
+            shapes_pixels_per_point: pixels_per_point,
+            tessellated: OnceLock::new(),
             textures_delta: Default::default(),
+            links: Default::default(),
+            accessibility_tree: None,
+            pdf_nodes: Vec::new(),
+            viewports: Vec::new(),
+            texture_loader,
         }
     }
 
-    /// Returns a duration after witch egui should repaint.
+    /// The number of pixels per point egui lays out and paints the ui with.
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// Change the number of pixels per point, from the next call to [`Self::run`] on.
+    ///
+    /// The screen rect passed to [`Self::run`] is in points, so it has to be scaled as well
+    /// to keep the size of the output in pixels.
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: f32) {
+        self.pixels_per_point = pixels_per_point;
+    }
+
+    /// Rasterize `ui` into an offscreen image of `size` points and register it as a texture
+    /// of this instance, so it can be shown inside the ui of this instance with e.g. `ui.image`.
+    ///
+    /// The image is rendered at the `pixels_per_point` of this instance, so the nested ui
+    /// stays sharp when it is shown at the returned size.
+    ///
+    /// With `texture` set to the id returned by an earlier call, that texture is updated
+    /// instead of registering a new one, e.g. to re-render the nested ui every frame.
+    /// Textures are owned by the caller and stay registered until they are freed with
+    /// [`Painter::free_native_texture`].
+    pub fn rasterize_ui_to_texture(
+        &mut self,
+        size: egui::Vec2,
+        ui: impl FnMut(&mut Ui),
+        texture: Option<TextureId>,
+    ) -> SizedTexture {
+        let size_px = pixel_size(
+            egui::Rect::from_min_size(Pos2::ZERO, size),
+            self.pixels_per_point,
+        );
+        let mut surface = rasterize_ui(
+            size_px,
+            ui,
+            Some(RasterizeOptions {
+                pixels_per_point: self.pixels_per_point,
+                ..Default::default()
+            }),
+        );
+        let image = surface.image_snapshot();
+        let id = match texture {
+            Some(id) => {
+                self.painter
+                    .update_native_texture(id, image, TextureOptions::LINEAR);
+                id
+            }
+            None => self
+                .painter
+                .register_native_texture(image, TextureOptions::LINEAR),
+        };
+        SizedTexture::new(id, size)
+    }
+
+    /// Run a frame of egui and return its platform output. Use [`Self::run_full`] to also
+    /// get e.g. the repaint delay.
+    ///
+    /// The screen rect of `input` is in points, see [`Self::pixels_per_point`].
     ///
     /// Call [`Self::paint`] later to paint.
     pub fn run(
@@ -110,7 +459,8 @@ impl EguiSkia {
         self.run_ui(input, |ui| run_ui(ui.ctx()))
     }
 
-    /// Returns a duration after witch egui should repaint.
+    /// Like [`Self::run`], but with a [`Ui`] for the whole screen. Use [`Self::run_ui_full`] to
+    /// also get e.g. the repaint delay.
     ///
     /// Call [`Self::paint`] later to paint.
     pub fn run_ui(
@@ -118,30 +468,447 @@ impl EguiSkia {
         input: egui::RawInput,
         run_ui: impl FnMut(&mut Ui),
     ) -> egui::PlatformOutput {
+        self.run_ui_full(input, run_ui).platform_output
+    }
+
+    /// Like [`Self::run`], but returns all of egui's output for the frame, e.g. to schedule
+    /// repaints or handle viewport commands.
+    pub fn run_full(
+        &mut self,
+        input: egui::RawInput,
+        mut run_ui: impl FnMut(&Context),
+    ) -> RunOutput {
+        self.run_ui_full(input, |ui| run_ui(ui.ctx()))
+    }
+
+    /// Like [`Self::run_ui`], but returns all of egui's output for the frame, e.g. to schedule
+    /// repaints or handle viewport commands.
+    pub fn run_ui_full(
+        &mut self,
+        mut input: egui::RawInput,
+        run_ui: impl FnMut(&mut Ui),
+    ) -> RunOutput {
+        profile_scope!("EguiSkia::run_ui");
+        // The scale factor of this instance overrides the one in `input`, so egui lays out
+        // with the same pixels per point the frame is painted with.
+        input
+            .viewports
+            .entry(input.viewport_id)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+
         let egui::FullOutput {
             platform_output,
             textures_delta,
             shapes,
-            ..
+            pixels_per_point,
+            viewport_output,
         } = self.egui_ctx.run_ui(input, run_ui);
 
+        // Immediate viewports run in the middle of the frame, so their texture changes
+        // come before the ones of the root viewport.
+        self.viewports = take_frames(&self.egui_ctx);
+        for viewport in &mut self.viewports {
+            self.textures_delta
+                .append(std::mem::take(&mut viewport.textures_delta));
+        }
+
         self.shapes = shapes;
+        self.shapes_pixels_per_point = pixels_per_point;
+        self.tessellated = OnceLock::new();
         self.textures_delta.append(textures_delta);
 
-        platform_output
+        // Deferred viewports run after the root viewport, so their texture changes come last.
+        render_deferred_viewports(&self.egui_ctx, &viewport_output);
+        let mut deferred = take_frames(&self.egui_ctx);
+        for viewport in &mut deferred {
+            self.textures_delta
+                .append(std::mem::take(&mut viewport.textures_delta));
+        }
+        self.viewports.extend(deferred);
+
+        for id in &self.textures_delta.free {
+            self.texture_loader.remove(*id);
+        }
+        for id in self.textures_delta.set.keys() {
+            if self.texture_loader.uri(*id).is_some() {
+                self.painter.set_evictable(*id, true);
+            }
+        }
+        self.links = platform_output
+            .accesskit_update
+            .as_ref()
+            .map(|update| {
+                widget_geometry(update, 1.0)
+                    .into_iter()
+                    .filter_map(|widget| Some((widget.rect, widget.url?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.accessibility_tree = platform_output
+            .accesskit_update
+            .clone()
+            .map(AccessibilityTree::new);
+        self.pdf_nodes = self
+            .accessibility_tree
+            .as_ref()
+            .map(AccessibilityTree::pdf_node_rects)
+            .unwrap_or_default();
+
+        RunOutput {
+            platform_output,
+            pixels_per_point,
+            viewport_output,
+        }
     }
 
     /// Paint the results of the last call to [`Self::run`].
+    ///
+    /// While accesskit is enabled on [`Self::egui_ctx`], links tagged with [`crate::tag_link`]
+    /// are covered with link annotations, which keeps them clickable on the canvas of a skia
+    /// PDF document, and the drawn content is marked with the elements of
+    /// [`AccessibilityTree::pdf_structure`]. Other canvases ignore the annotations and marks.
     pub fn paint(&mut self, canvas: &Canvas) {
-        let shapes = std::mem::take(&mut self.shapes);
+        self.paint_with_stats(canvas);
+    }
+
+    /// Like [`Self::paint`], but returns statistics about the painted frame.
+    pub fn paint_with_stats(&mut self, canvas: &Canvas) -> RenderStats {
+        profile_scope!("EguiSkia::paint");
+        let frame = self.take_tessellated();
         let textures_delta = std::mem::take(&mut self.textures_delta);
-        let clipped_primitives = self.egui_ctx.tessellate(shapes, self.pixels_per_point);
-        self.painter.paint_and_update_textures(
+        let dpi = frame.pixels_per_point;
+        self.painter.set_screen_size_px(Some(self.screen_size_px()));
+        let mut stats = self.painter.paint_tagged(
             canvas,
-            self.egui_ctx.pixels_per_point(),
-            clipped_primitives,
+            dpi,
+            frame.clipped_primitives,
             textures_delta,
+            &self.pdf_nodes,
         );
+        self.annotate_links(canvas, dpi);
+        stats.tessellation_time = frame.tessellation_time;
+        self.forget_evicted_images();
+        stats
+    }
+
+    /// Record the results of the last call to [`Self::run`] into a [`Picture`], which can be
+    /// replayed onto other canvases and at other scales without running egui again.
+    ///
+    /// The picture is in pixels and culled to the screen. It references the images of the
+    /// textures, so it stays valid when they are freed later.
+    pub fn paint_to_picture(&mut self) -> Picture {
+        profile_scope!("EguiSkia::paint_to_picture");
+        let screen = self.egui_ctx.viewport_rect() * self.shapes_pixels_per_point;
+        let bounds = skia_safe::Rect::new(screen.min.x, screen.min.y, screen.max.x, screen.max.y);
+
+        let mut recorder = PictureRecorder::new();
+        let canvas = recorder.begin_recording(bounds, false);
+        self.paint(canvas);
+        recorder
+            .finish_recording_as_picture(Some(&bounds))
+            .expect("Failed to record picture")
+    }
+
+    /// Like [`Self::paint_to_picture`], but returns the picture serialized as `.skp` bytes,
+    /// which can be loaded with [`Picture::from_data`] or opened in skia's debugger.
+    ///
+    /// Skia only serializes images that carry encoded data, so the textures are recorded
+    /// as PNG encoded images and embedded in the bytes.
+    pub fn paint_to_skp(&mut self) -> skia_safe::Data {
+        self.painter.set_encode_textures(true);
+        let picture = self.paint_to_picture();
+        self.painter.set_encode_textures(false);
+        picture.serialize()
+    }
+
+    /// Paint the part of the results of the last call to [`Self::run`] inside `crop`, which
+    /// is in points, with the top left corner of `crop` at the origin of `canvas`.
+    ///
+    /// Primitives that do not draw into `crop` are skipped.
+    pub fn paint_crop(&mut self, canvas: &Canvas, crop: egui::Rect) -> RenderStats {
+        profile_scope!("EguiSkia::paint_crop");
+        let frame = self.take_tessellated();
+        let textures_delta = std::mem::take(&mut self.textures_delta);
+        let dpi = frame.pixels_per_point;
+
+        let primitives = cull(&with_visible_rects(frame.clipped_primitives), crop);
+
+        let save_count = canvas.save();
+        canvas.translate((-crop.min.x * dpi, -crop.min.y * dpi));
+        canvas.clip_rect(
+            skia_safe::Rect::new(
+                crop.min.x * dpi,
+                crop.min.y * dpi,
+                crop.max.x * dpi,
+                crop.max.y * dpi,
+            ),
+            skia_safe::ClipOp::default(),
+            true,
+        );
+        self.painter.set_screen_size_px(Some(self.screen_size_px()));
+        let mut stats =
+            self.painter
+                .paint_tagged(canvas, dpi, primitives, textures_delta, &self.pdf_nodes);
+        self.annotate_links(canvas, dpi);
+        canvas.restore_to_count(save_count);
+
+        stats.tessellation_time = frame.tessellation_time;
+        self.forget_evicted_images();
+        stats
+    }
+
+    /// Paint the results of the last call to [`Self::run`] into an image of `size` pixels,
+    /// tile by tile, calling `on_tile` with the rect of every tile in the full image and a
+    /// surface holding its pixels. Tiles are painted row by row, from the top left.
+    ///
+    /// Only one tile is held in memory at a time, and only the primitives that draw into a
+    /// tile are painted into it.
+    pub fn paint_tiled(
+        &mut self,
+        size: (i32, i32),
+        tile_size: (i32, i32),
+        mut on_tile: impl FnMut(IRect, &mut Surface),
+    ) {
+        profile_scope!("EguiSkia::paint_tiled");
+        let frame = self.take_tessellated();
+        let mut textures_delta = std::mem::take(&mut self.textures_delta);
+        let dpi = frame.pixels_per_point;
+        self.painter.set_screen_size_px(Some(self.screen_size_px()));
+
+        let primitives = with_visible_rects(frame.clipped_primitives);
+        let (tile_width, tile_height) = (tile_size.0.max(1), tile_size.1.max(1));
+        self.paint_several(|this| {
+            for y in (0..size.1).step_by(tile_height as usize) {
+                for x in (0..size.0).step_by(tile_width as usize) {
+                    let tile = IRect::from_xywh(
+                        x,
+                        y,
+                        tile_width.min(size.0 - x),
+                        tile_height.min(size.1 - y),
+                    );
+                    let tile_in_points = egui::Rect::from_min_max(
+                        Pos2::new(tile.left as f32 / dpi, tile.top as f32 / dpi),
+                        Pos2::new(tile.right as f32 / dpi, tile.bottom as f32 / dpi),
+                    );
+
+                    let mut surface =
+                        raster_n32_premul(tile.size()).expect("Failed to create surface");
+                    let canvas = surface.canvas();
+                    canvas.translate((-x as f32, -y as f32));
+
+                    this.painter.paint_and_update_textures(
+                        canvas,
+                        dpi,
+                        cull(&primitives, tile_in_points),
+                        std::mem::take(&mut textures_delta),
+                    );
+
+                    on_tile(tile, &mut surface);
+                }
+            }
+            // Without tiles, e.g. for an empty size, the delta is applied by the next paint.
+            this.textures_delta.append(textures_delta);
+        });
+    }
+
+    /// Where each widget of the frame of `platform_output` ended up, with rects in pixels.
+    ///
+    /// egui only reports widgets while accesskit is enabled, so call
+    /// `self.egui_ctx.enable_accesskit()` before [`Self::run`], otherwise this is empty.
+    /// Widgets outside the screen are included.
+    pub fn widget_geometry(&self, platform_output: &egui::PlatformOutput) -> Vec<WidgetGeometry> {
+        platform_output
+            .accesskit_update
+            .as_ref()
+            .map(|update| widget_geometry(update, self.shapes_pixels_per_point))
+            .unwrap_or_default()
+    }
+
+    /// The accessibility tree of the frame of the last call to [`Self::run`], with the
+    /// structure, roles and text of its widgets.
+    ///
+    /// egui only builds the tree while accesskit is enabled, so call
+    /// `self.egui_ctx.enable_accesskit()` before [`Self::run`], otherwise this is `None`.
+    pub fn accessibility_tree(&self) -> Option<&AccessibilityTree> {
+        self.accessibility_tree.as_ref()
+    }
+
+    /// The rect of the widget with `id` in the last call to [`Self::run`], in points.
+    ///
+    /// Widgets tagged with [`tag_widget`] are found by the id of their tag. Other widgets are
+    /// looked up in the [`Self::accessibility_tree`], so call `self.egui_ctx.enable_accesskit()`
+    /// before [`Self::run`] to find them. egui's own widget rects lag a frame behind.
+    pub fn widget_rect(&self, id: egui::Id) -> Option<egui::Rect> {
+        self.egui_ctx
+            .data(|data| data.get_temp::<egui::Rect>(tag_id(id)))
+            .or_else(|| self.accessibility_tree.as_ref()?.widget_rect(id))
+    }
+
+    /// Paint each widget with one of `ids` from the last call to [`Self::run`] into its own
+    /// surface, grown by `padding` points on every side. See [`Self::widget_rect`].
+    ///
+    /// The frame is tessellated once for all widgets. Ids without a widget are skipped.
+    pub fn paint_widgets(&mut self, ids: &[egui::Id], padding: f32) -> Vec<(egui::Id, Surface)> {
+        profile_scope!("EguiSkia::paint_widgets");
+        let rects: Vec<_> = ids
+            .iter()
+            .filter_map(|&id| Some((id, self.widget_rect(id)?.expand(padding))))
+            .collect();
+
+        let frame = self.take_tessellated();
+        let mut textures_delta = std::mem::take(&mut self.textures_delta);
+        let dpi = frame.pixels_per_point;
+        self.painter.set_screen_size_px(Some(self.screen_size_px()));
+
+        let primitives = with_visible_rects(frame.clipped_primitives);
+        self.paint_several(|this| {
+            let surfaces = rects
+                .into_iter()
+                .map(|(id, rect)| {
+                    let mut surface =
+                        raster_n32_premul(pixel_size(rect, dpi)).expect("Failed to create surface");
+                    let canvas = surface.canvas();
+                    canvas.translate((-rect.min.x * dpi, -rect.min.y * dpi));
+
+                    this.painter.paint_and_update_textures(
+                        canvas,
+                        dpi,
+                        cull(&primitives, rect),
+                        std::mem::take(&mut textures_delta),
+                    );
+
+                    (id, surface)
+                })
+                .collect();
+            // Without widgets to paint, the delta is applied by the next paint.
+            this.textures_delta.append(textures_delta);
+            surfaces
+        })
+    }
+
+    fn annotate_links(&self, canvas: &Canvas, dpi: f32) {
+        for (rect, url) in &self.links {
+            // Skia expects urls as C strings.
+            if url.contains('\0') {
+                continue;
+            }
+            canvas.annotate_rect_with_url(
+                skia_safe::Rect::new(
+                    rect.min.x * dpi,
+                    rect.min.y * dpi,
+                    rect.max.x * dpi,
+                    rect.max.y * dpi,
+                ),
+                &skia_safe::Data::new_str(url),
+            );
+        }
+    }
+
+    /// Run viewports shown with [`Context::show_viewport_immediate`] as frames of their own,
+    /// which [`Self::paint_viewports`] paints into separate surfaces, instead of embedding
+    /// them as windows in the root viewport. egui then no longer embeds viewports shown with
+    /// [`Context::show_viewport_deferred`] either, so [`Self::run`] runs them as frames of
+    /// their own after the root viewport.
+    ///
+    /// egui keeps the renderer of immediate viewports per thread, so call this on the thread
+    /// that runs the ui.
+    pub fn enable_immediate_viewports(&self) {
+        render_immediate_viewports(&self.egui_ctx);
+    }
+
+    /// Paint the root viewport and each other viewport of the last call to [`Self::run`]
+    /// into its own surface. Viewports are sized by the `inner_size` of their builder, or
+    /// like the root viewport if it is not set.
+    ///
+    /// See [`Self::enable_immediate_viewports`].
+    pub fn paint_viewports(&mut self) -> egui::OrderedViewportIdMap<Surface> {
+        profile_scope!("EguiSkia::paint_viewports");
+        self.paint_several(|this| {
+            let mut surfaces = egui::OrderedViewportIdMap::default();
+
+            let root_rect = this.egui_ctx.viewport_rect();
+            let mut surface =
+                raster_n32_premul(pixel_size(root_rect, this.shapes_pixels_per_point))
+                    .expect("Failed to create surface");
+            this.paint(surface.canvas());
+            surfaces.insert(egui::ViewportId::ROOT, surface);
+
+            for viewport in std::mem::take(&mut this.viewports) {
+                let primitives = {
+                    profile_scope!("tessellate");
+                    this.egui_ctx
+                        .tessellate(viewport.shapes, viewport.pixels_per_point)
+                };
+                let size = pixel_size(viewport.screen_rect, viewport.pixels_per_point);
+                let mut surface = raster_n32_premul(size).expect("Failed to create surface");
+                this.painter
+                    .set_screen_size_px(Some([size.0 as u32, size.1 as u32]));
+                this.painter.paint_and_update_textures(
+                    surface.canvas(),
+                    viewport.pixels_per_point,
+                    primitives,
+                    Default::default(),
+                );
+                surfaces.insert(viewport.id, surface);
+            }
+            surfaces
+        })
+    }
+
+    /// Runs `paint`, which paints several times, with the texture memory budget applied only
+    /// once all paints are done. Textures drawn in a later paint are then not evicted after an
+    /// earlier one, and textures drawn in any of the paints are kept.
+    fn paint_several<R>(&mut self, paint: impl FnOnce(&mut Self) -> R) -> R {
+        let texture_memory_budget = self.painter.texture_memory_budget();
+        self.painter.set_texture_memory_budget(None);
+        let first_frame = self.painter.next_frame();
+
+        let result = paint(self);
+
+        self.painter
+            .set_texture_memory_budget(texture_memory_budget);
+        if let Some(budget) = texture_memory_budget {
+            self.painter.trim_textures_since(budget, first_frame);
+            self.forget_evicted_images();
+        }
+        result
+    }
+
+    /// The size in pixels of the screen of the last frame, which is painted in parts by e.g.
+    /// [`Self::paint_tiled`].
+    fn screen_size_px(&self) -> [u32; 2] {
+        let (width, height) =
+            pixel_size(this.egui_ctx.viewport_rect(), this.shapes_pixels_per_point);
+        [width as u32, height as u32]
+    }
+
+    fn take_tessellated(&mut self) -> TessellatedFrame {
+        let shapes = std::mem::take(&mut self.shapes);
+        match self.tessellated.take() {
+            Some(frame) => frame,
+            None => TessellatedFrame::new(&self.egui_ctx, shapes, self.shapes_pixels_per_point),
+        }
+    }
+
+    /// Evict the least recently used textures, except for the font atlas, until at most
+    /// `max_bytes` are held. See [`Painter::trim_textures`].
+    ///
+    /// Images that egui loaded and that were evicted are loaded again the next time they are shown.
+    pub fn trim_textures(&mut self, max_bytes: usize) -> usize {
+        let evicted = self.painter.trim_textures(max_bytes);
+        self.forget_evicted_images();
+        evicted
+    }
+
+    fn forget_evicted_images(&mut self) {
+        // egui uploads its textures only once, so evicted images have to be loaded into new
+        // textures. Their decoded images are still cached by the image loaders.
+        for id in self.painter.take_evicted_textures() {
+            if let Some(uri) = self.texture_loader.uri(id) {
+                self.texture_loader.forget(&uri);
+            }
+        }
     }
 
     /// Check if all textures required for rendering are currently loaded.
@@ -149,29 +916,22 @@ impl EguiSkia {
     /// This checks if all textures referenced in the current shapes/primitives
     /// are available in the painter's texture cache OR in the pending texture delta.
     ///
+    /// The shapes of the last frame are tessellated on the first call, the result
+    /// is reused by later calls and by [`Self::paint`].
+    ///
     /// Returns `true` if all textures are ready, `false` if any are missing.
     pub fn are_textures_loaded(&self) -> bool {
-        let clipped_primitives = self
-            .egui_ctx
-            .tessellate(self.shapes.clone(), self.pixels_per_point);
-
-        // Check if all textures are either already loaded or in the pending delta
-        clipped_primitives.iter().all(|primitive| {
-            match &primitive.primitive {
-                egui::epaint::Primitive::Mesh(mesh) => {
-                    mesh.clone().split_to_u16().iter().all(|m| {
-                        let texture_id = m.texture_id;
-                        // Check if texture is already in painter OR in pending delta
-                        self.painter.has_texture(&texture_id)
-                            || self
-                                .textures_delta
-                                .set
-                                .iter()
-                                .any(|(id, _)| id == &texture_id)
-                    })
-                }
-                egui::epaint::Primitive::Callback(_) => true,
-            }
+        let frame = self.tessellated.get_or_init(|| {
+            TessellatedFrame::new(
+                &self.egui_ctx,
+                self.shapes.clone(),
+                self.shapes_pixels_per_point,
+            )
+        });
+
+        // Check if texture is already in painter OR in pending delta
+        frame.texture_ids.iter().all(|texture_id| {
+            self.painter.has_texture(texture_id) || self.textures_delta.set.contains_key(texture_id)
         })
     }
 
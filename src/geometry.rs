@@ -0,0 +1,97 @@
+use egui::accesskit::{Node, Role, TreeUpdate};
+use egui::{Pos2, Rect};
+
+/// Where a widget ended up in a rendered image, e.g. to build an image map of its links
+/// and buttons. See [`crate::EguiSkia::widget_geometry`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WidgetGeometry {
+    /// The [`egui::Id::value`] of the widget.
+    pub id: u64,
+    /// The accessibility role of the widget, e.g. `Button`, `Link`, `Label` or `CheckBox`,
+    /// or `Other` for roles egui does not give its widgets.
+    pub kind: String,
+    /// The text of the widget, if it has any.
+    pub label: Option<String>,
+    /// The url of widgets tagged with [`tag_link`].
+    pub url: Option<String>,
+    /// The rect of the widget in output pixels.
+    pub rect: Rect,
+}
+
+/// Record `url` as the target of the widget of `response`, so it shows up in
+/// [`WidgetGeometry::url`]. egui does not expose the url of an [`egui::Hyperlink`],
+/// so tag it with the same url:
+///
+/// ```ignore
+/// tag_link(&ui.hyperlink_to("egui", url), url);
+/// ```
+///
+/// Does nothing unless accesskit is enabled on the context of `response`.
+pub fn tag_link(response: &egui::Response, url: impl Into<String>) {
+    let url = url.into();
+    response
+        .ctx
+        .accesskit_node_builder(response.id, |node| node.set_url(url));
+}
+
+/// Collects the widgets of `update`, with their rects scaled by `pixels_per_point`.
+pub(crate) fn widget_geometry(update: &TreeUpdate, pixels_per_point: f32) -> Vec<WidgetGeometry> {
+    let root = update.tree.as_ref().map(|tree| tree.root);
+
+    update
+        .nodes
+        .iter()
+        .filter(|(id, node)| {
+            Some(*id) != root && !matches!(node.role(), Role::GenericContainer | Role::TextRun)
+        })
+        .filter_map(|(id, node)| {
+            let rect = node_rect(node)?;
+            Some(WidgetGeometry {
+                id: id.0,
+                kind: widget_kind(node.role()).to_owned(),
+                // egui puts the text of labels into their value.
+                label: node.label().or(node.value()).map(ToOwned::to_owned),
+                url: node.url().map(ToOwned::to_owned),
+                rect: rect * pixels_per_point,
+            })
+        })
+        .collect()
+}
+
+/// The bounds of `node`, in the units of its tree.
+pub(crate) fn node_rect(node: &Node) -> Option<Rect> {
+    let bounds = node.bounds()?;
+    Some(Rect::from_min_max(
+        Pos2::new(bounds.x0 as f32, bounds.y0 as f32),
+        Pos2::new(bounds.x1 as f32, bounds.y1 as f32),
+    ))
+}
+
+/// The [`WidgetGeometry::kind`] of `role`, also used by [`crate::AccessibilityTree::outline`].
+/// These names are part of the serialized geometry, so they are spelled out instead of relying
+/// on the `Debug` output of AccessKit.
+pub(crate) fn widget_kind(role: Role) -> &'static str {
+    match role {
+        Role::Button => "Button",
+        Role::CheckBox => "CheckBox",
+        Role::ColorWell => "ColorWell",
+        Role::ComboBox => "ComboBox",
+        Role::Image => "Image",
+        Role::Label => "Label",
+        Role::Link => "Link",
+        Role::MultilineTextInput => "MultilineTextInput",
+        Role::Pane => "Pane",
+        Role::PasswordInput => "PasswordInput",
+        Role::ProgressIndicator => "ProgressIndicator",
+        Role::RadioButton => "RadioButton",
+        Role::RadioGroup => "RadioGroup",
+        Role::ScrollBar => "ScrollBar",
+        Role::Slider => "Slider",
+        Role::SpinButton => "SpinButton",
+        Role::Splitter => "Splitter",
+        Role::TextInput => "TextInput",
+        Role::Window => "Window",
+        _ => "Other",
+    }
+}
@@ -1,7 +1,25 @@
 extern crate core;
 
+/// Opens a [`profiling`] scope for the rest of the block when the `profiling` feature is enabled.
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        profiling::scope!($name);
+    };
+}
+
+mod accessibility;
+mod debug;
 mod egui_skia;
+mod geometry;
 mod painter;
+mod texture_loader;
+mod texture_store;
+mod viewports;
 
+pub use accessibility::AccessibilityTree;
+pub use debug::DebugOptions;
 pub use egui_skia::*;
-pub use painter::EguiSkiaPaintCallback;
+pub use geometry::{WidgetGeometry, tag_link};
+pub use painter::{EguiSkiaPaintCallback, Painter, RenderStats};
+pub use texture_store::SharedTextureStore;
@@ -1,45 +1,361 @@
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 
 use egui::epaint::Primitive;
-use egui::{ClippedPrimitive, ImageData, Pos2, TextureId, TexturesDelta};
-use skia_safe::surfaces::raster_n32_premul;
+use egui::{
+    ClippedPrimitive, ColorImage, ImageData, PaintCallbackInfo, Pos2, TextureId, TextureOptions,
+    TexturesDelta, epaint::ImageDelta,
+};
+use skia_safe::surfaces::{self, raster_n32_premul};
 use skia_safe::vertices::VertexMode;
 use skia_safe::{
-    BlendMode, Canvas, ClipOp, Color, ConditionallySend, Data, Drawable, Image, ImageInfo, Paint,
-    PictureRecorder, Point, Rect, Sendable, Vertices, scalar,
+    BlendMode, Canvas, ClipOp, Color, ConditionallySend, Data, Drawable, EncodedImageFormat, ISize,
+    Image, ImageInfo, Paint, PictureRecorder, Point, Rect, Sendable, Surface, Vertices,
 };
 
+use crate::debug::{self, DebugOptions, DebugPrimitive};
+use crate::texture_store::SharedTextureStore;
+
+/// Statistics about a single call to [`Painter::paint_and_update_textures`].
+#[derive(Clone, Debug, Default)]
+pub struct RenderStats {
+    /// Number of clipped primitives produced by tessellation.
+    pub clipped_primitives: usize,
+    /// Number of meshes drawn, after splitting them with `split_to_u16`.
+    pub meshes: usize,
+    pub vertices: usize,
+    pub indices: usize,
+    /// Number of paint callbacks executed.
+    pub callbacks: usize,
+    /// Number of textures that were uploaded as a whole.
+    pub textures_uploaded: usize,
+    /// Number of partial updates applied to existing textures.
+    pub textures_updated: usize,
+    pub textures_freed: usize,
+    /// Number of textures evicted to stay within [`Painter::set_texture_memory_budget`].
+    pub textures_evicted: usize,
+    /// Bytes of texture memory held by the painter after painting.
+    pub texture_bytes: usize,
+    /// Time spent tessellating shapes. Only filled in by [`crate::EguiSkia`].
+    pub tessellation_time: Duration,
+    /// Time spent applying the texture delta, including building the images and mipmaps
+    /// that are drawn.
+    pub texture_upload_time: Duration,
+    pub draw_time: Duration,
+}
+
+/// The number of evicted textures a painter remembers for [`Painter::take_evicted_textures`].
+const MAX_EVICTED_TEXTURES: usize = 1024;
+
 struct PaintHandle {
-    paint: Paint,
+    /// The image drawn by `paint`, with mipmaps if the options ask for them.
     image: Image,
+    paint: Paint,
+    options: TextureOptions,
+    /// The pixels of a texture that received partial updates, e.g. the font atlas.
+    /// Updates are written into it in place, and `image` is a snapshot of it.
+    surface: Option<TextureSurface>,
+    /// The image of a [`SharedTextureStore`] that `image` was built from. Holding it marks
+    /// it as used, see [`SharedTextureStore::purge_unused`].
+    shared: Option<Image>,
+    /// The last frame this texture was uploaded or drawn in, used for LRU eviction.
+    last_used: u64,
+}
+
+/// A surface that is only accessed through `&mut PaintHandle`.
+struct TextureSurface(Sendable<Surface>);
+
+unsafe impl Sync for TextureSurface {}
+
+/// Bytes of pixel memory of `image`, including its mipmaps.
+fn image_byte_size(image: &Image) -> usize {
+    let bytes = image.image_info().compute_min_byte_size();
+    if image.has_mipmaps() {
+        bytes + bytes / 3
+    } else {
+        bytes
+    }
+}
+
+fn rgba_info(size: ISize) -> ImageInfo {
+    ImageInfo::new(
+        size,
+        skia_safe::ColorType::RGBA8888,
+        skia_safe::AlphaType::Premul,
+        None,
+    )
+}
+
+/// The part of the screen `primitive` draws to, in points: the bounds of its mesh or the rect
+/// of its callback, clipped to its clip rect.
+pub(crate) fn visible_rect(primitive: &ClippedPrimitive) -> egui::Rect {
+    let bounds = match &primitive.primitive {
+        Primitive::Mesh(mesh) => mesh.calc_bounds(),
+        Primitive::Callback(callback) => callback.rect,
+    };
+    bounds.intersect(primitive.clip_rect)
+}
+
+/// The id of the innermost element of `pdf_nodes` whose widget contains what `primitive`
+/// draws, or 0 for none.
+fn pdf_node_id(pdf_nodes: &[(i32, egui::Rect)], primitive: &ClippedPrimitive) -> i32 {
+    let bounds = visible_rect(primitive);
+    pdf_nodes
+        .iter()
+        // Anti-aliasing makes meshes a little larger than the rects of their widgets.
+        .filter(|(_, rect)| rect.expand(1.0).contains_rect(bounds))
+        .min_by(|(_, a), (_, b)| a.area().total_cmp(&b.area()))
+        .map_or(0, |(node_id, _)| *node_id)
+}
+
+/// Create a raster image from RGBA8888 premultiplied `pixels`.
+pub(crate) fn image_from_pixels(pixels: &[u8], size: [usize; 2]) -> Image {
+    let [width, height] = size;
+    skia_safe::images::raster_from_data(
+        &rgba_info(ISize::new(width as i32, height as i32)),
+        Data::new_copy(pixels),
+        width * 4,
+    )
+    .unwrap()
+}
+
+impl PaintHandle {
+    fn new(image: Image, options: TextureOptions) -> Self {
+        profile_scope!("build texture image");
+        let (image, paint) = Self::build_paint(image, options);
+        Self {
+            image,
+            paint,
+            options,
+            surface: None,
+            shared: None,
+            last_used: 0,
+        }
+    }
+
+    fn shared(image: Image, options: TextureOptions) -> Self {
+        Self {
+            shared: Some(image.clone()),
+            ..Self::new(image, options)
+        }
+    }
+
+    fn write_region(
+        self,
+        pos: [usize; 2],
+        color_image: &ColorImage,
+        options: TextureOptions,
+    ) -> Self {
+        let Self {
+            image,
+            paint,
+            surface,
+            last_used,
+            ..
+        } = self;
+
+        let mut surface = match surface {
+            Some(TextureSurface(surface)) => surface.into_inner(),
+            // The first update copies the texture into a surface, later ones write into it.
+            None => {
+                let mut surface =
+                    surfaces::raster(&rgba_info(image.dimensions()), None, None).unwrap();
+                surface.canvas().draw_image(&image, (0, 0), None);
+                surface
+            }
+        };
+
+        // Without other references to the last snapshot, the surface is written in place
+        // instead of being copied first.
+        drop((image, paint));
+        let [x, y] = pos;
+        // Pixels outside of the texture are clipped.
+        let _ = surface.canvas().write_pixels(
+            &rgba_info(ISize::new(
+                color_image.width() as i32,
+                color_image.height() as i32,
+            )),
+            color_image.as_raw(),
+            color_image.width() * 4,
+            (x as i32, y as i32),
+        );
+
+        let mut handle = Self::new(surface.image_snapshot(), options);
+        handle.surface = surface.wrap_send().ok().map(TextureSurface);
+        handle.last_used = last_used;
+        handle
+    }
+
+    /// Bytes of pixel memory held by the texture. The image of a surface shares its pixels,
+    /// and of a shared image only the mipmaps built for it are counted.
+    fn byte_size(&self) -> usize {
+        let bytes = image_byte_size(&self.image);
+        if self.shared.is_some() {
+            bytes - self.image.image_info().compute_min_byte_size()
+        } else {
+            bytes
+        }
+    }
+
+    fn build_paint(image: Image, options: TextureOptions) -> (Image, Paint) {
+        // Without mipmaps, downscaled images alias badly, whatever the mipmap mode.
+        let image = match options.mipmap_mode {
+            Some(_) if !image.has_mipmaps() => image.with_default_mipmaps().unwrap_or(image),
+            _ => image,
+        };
+        let paint = Self::image_paint(&image, options);
+        (image, paint)
+    }
+
+    /// Like `paint`, but with the texture encoded as PNG. Pictures only keep images with
+    /// encoded data when they are serialized.
+    fn encoded_paint(&self) -> Paint {
+        profile_scope!("encode texture image");
+        self.image
+            .encode(None, EncodedImageFormat::PNG, 100)
+            .and_then(|data| skia_safe::images::deferred_from_encoded_data(data, None))
+            .map_or_else(
+                || self.paint.clone(),
+                |image| Self::image_paint(&image, self.options),
+            )
+    }
+
+    fn image_paint(image: &Image, options: TextureOptions) -> Paint {
+        let local_matrix =
+            skia_safe::Matrix::scale((1.0 / image.width() as f32, 1.0 / image.height() as f32));
+
+        let sampling_options = {
+            use egui::TextureFilter;
+            let filter_mode = match options.magnification {
+                TextureFilter::Nearest => skia_safe::FilterMode::Nearest,
+                TextureFilter::Linear => skia_safe::FilterMode::Linear,
+            };
+            let mm_mode = match options.mipmap_mode {
+                None => skia_safe::MipmapMode::None,
+                Some(TextureFilter::Nearest) => skia_safe::MipmapMode::Nearest,
+                Some(TextureFilter::Linear) => skia_safe::MipmapMode::Linear,
+            };
+
+            skia_safe::SamplingOptions::new(filter_mode, mm_mode)
+        };
+        let tile_mode = match options.wrap_mode {
+            egui::TextureWrapMode::ClampToEdge => skia_safe::TileMode::Clamp,
+            egui::TextureWrapMode::Repeat => skia_safe::TileMode::Repeat,
+            egui::TextureWrapMode::MirroredRepeat => skia_safe::TileMode::Mirror,
+        };
+
+        let shader = image
+            .to_shader((tile_mode, tile_mode), sampling_options, &local_matrix)
+            .unwrap();
+
+        let mut paint = Paint::default();
+        paint.set_shader(shader);
+        paint.set_color(Color::WHITE);
+        paint
+    }
 }
 
 pub struct Painter {
     paints: AHashMap<TextureId, PaintHandle>,
+    next_user_texture_id: u64,
+    /// Incremented for every call to [`Painter::paint_and_update_textures`].
+    frame: u64,
+    texture_memory_budget: Option<usize>,
+    /// Textures that can be restored after they were evicted, see [`Painter::set_evictable`].
+    evictable: AHashSet<TextureId>,
+    /// Textures evicted since the last call to [`Painter::take_evicted_textures`].
+    evicted: Vec<TextureId>,
+    evictions: usize,
+    shared_textures: Option<SharedTextureStore>,
+    debug_options: Option<DebugOptions>,
+    debug_layer: Option<Image>,
+    /// Draw textures as PNG encoded images, see [`Painter::set_encode_textures`].
+    encode_textures: bool,
+    /// See [`Painter::set_screen_size_px`].
+    screen_size_px: Option<[u32; 2]>,
 }
 
 impl Painter {
     pub fn new() -> Painter {
         Self {
             paints: AHashMap::new(),
+            next_user_texture_id: 0,
+            frame: 0,
+            texture_memory_budget: None,
+            evictable: AHashSet::new(),
+            evicted: Vec::new(),
+            evictions: 0,
+            shared_textures: None,
+            debug_options: None,
+            debug_layer: None,
+            encode_textures: false,
+            screen_size_px: None,
         }
     }
 
+    /// Register a skia [`Image`] as a texture, so it can be shown with e.g. `ui.image`
+    /// without copying it through [`egui::ColorImage`].
+    ///
+    /// The returned id stays valid until [`Self::free_native_texture`] is called.
+    pub fn register_native_texture(&mut self, image: Image, options: TextureOptions) -> TextureId {
+        let id = TextureId::User(self.next_user_texture_id);
+        self.next_user_texture_id += 1;
+        self.insert_texture(id, PaintHandle::new(image, options));
+        id
+    }
+
+    /// Replace the image of a texture registered with [`Self::register_native_texture`].
+    pub fn update_native_texture(&mut self, id: TextureId, image: Image, options: TextureOptions) {
+        self.insert_texture(id, PaintHandle::new(image, options));
+    }
+
+    /// Free a texture registered with [`Self::register_native_texture`].
+    pub fn free_native_texture(&mut self, id: TextureId) {
+        self.paints.remove(&id);
+        self.evictable.remove(&id);
+    }
+
+    /// Look up textures uploaded by egui in `store`, and add them to it, so identical images
+    /// are only held once by all painters sharing the store.
+    pub fn set_shared_texture_store(&mut self, store: Option<SharedTextureStore>) {
+        self.shared_textures = store;
+    }
+
+    /// Enable or disable the debug overlay, which is drawn after the normal painting.
+    pub fn set_debug_options(&mut self, debug_options: Option<DebugOptions>) {
+        self.debug_options = debug_options;
+    }
+
+    /// Set the size of the screen in pixels that paint callbacks get in
+    /// [`PaintCallbackInfo::screen_size_px`], which egui clamps their pixel rects to.
+    ///
+    /// Defaults to the size of the canvas painted onto, which is only right while the canvas
+    /// covers the whole screen, not e.g. a tile or a crop of it. [`crate::EguiSkia`] sets it.
+    pub fn set_screen_size_px(&mut self, screen_size_px: Option<[u32; 2]>) {
+        self.screen_size_px = screen_size_px;
+    }
+
+    /// Draw textures as PNG encoded images, so they are kept when a picture recorded from
+    /// the painted canvas is serialized. Encoding happens in every paint, so only enable this
+    /// while recording pictures to serialize.
+    pub(crate) fn set_encode_textures(&mut self, encode_textures: bool) {
+        self.encode_textures = encode_textures;
+    }
+
+    /// Take the overlay of the last paint, if [`DebugOptions::separate_layer`] is set.
+    pub fn take_debug_layer(&mut self) -> Option<Image> {
+        self.debug_layer.take()
+    }
+
     /// Check if all textures referenced in the primitives are loaded
     pub fn all_textures_loaded(&self, primitives: &[ClippedPrimitive]) -> bool {
         primitives.iter().all(|primitive| {
             match &primitive.primitive {
-                Primitive::Mesh(mesh) => {
-                    // Check if the texture for this mesh is loaded
-                    // We need to clone because split_to_u16() consumes the mesh
-                    mesh.clone()
-                        .split_to_u16()
-                        .iter()
-                        .all(|m| self.paints.contains_key(&m.texture_id))
-                }
+                // split_to_u16() keeps the texture of the mesh, so there is no need to split here
+                Primitive::Mesh(mesh) => self.paints.contains_key(&mesh.texture_id),
                 Primitive::Callback(_) => true, // Callbacks don't use textures from the map
             }
         })
@@ -50,111 +366,177 @@ impl Painter {
         self.paints.contains_key(texture_id)
     }
 
-    pub fn paint_and_update_textures(
-        &mut self,
-        canvas: &Canvas,
-        dpi: f32,
-        primitives: Vec<ClippedPrimitive>,
-        mut textures_delta: TexturesDelta,
-    ) {
-        for (id, image_deltas) in &textures_delta.set {
-            for image_delta in image_deltas {
-                let delta_image = match &image_delta.image {
-                    ImageData::Color(color_image) => skia_safe::images::raster_from_data(
-                        &ImageInfo::new(
-                            skia_safe::ISize::new(
-                                color_image.width() as i32,
-                                color_image.height() as i32,
-                            ),
-                            skia_safe::ColorType::RGBA8888,
-                            skia_safe::AlphaType::Premul,
-                            None,
-                        ),
-                        Data::new_copy(
-                            color_image
-                                .pixels
-                                .iter()
-                                .flat_map(|p| p.to_array())
-                                .collect::<Vec<_>>()
-                                .as_slice(),
-                        ),
-                        color_image.width() * 4,
-                    )
-                    .unwrap(),
-                };
+    /// Bytes of texture memory currently held by the painter.
+    pub fn texture_bytes(&self) -> usize {
+        self.paints.values().map(PaintHandle::byte_size).sum()
+    }
 
-                let image = match image_delta.pos {
-                    None => delta_image,
-                    Some(pos) => {
-                        let old_image = self.paints.remove(id).unwrap().image;
+    /// Allow or forbid evicting the texture `id` to stay within the memory budget.
+    ///
+    /// Only mark textures that can be restored: egui uploads its textures only once, and
+    /// evicted native textures have to be registered again after
+    /// [`Self::take_evicted_textures`] reported them. [`crate::EguiSkia`] marks the images
+    /// loaded by egui's image loaders, which it has egui load again.
+    pub fn set_evictable(&mut self, id: TextureId, evictable: bool) {
+        if evictable {
+            self.evictable.insert(id);
+        } else {
+            self.evictable.remove(&id);
+        }
+    }
 
-                        let mut surface = raster_n32_premul(skia_safe::ISize::new(
-                            old_image.width(),
-                            old_image.height(),
-                        ))
-                        .unwrap();
+    /// Limit the texture memory held by the painter.
+    ///
+    /// After every paint, the least recently used textures marked with
+    /// [`Self::set_evictable`] are evicted until at most `budget` bytes are held, or no
+    /// such texture is left. The font atlas and textures drawn in that paint are never
+    /// evicted.
+    pub fn set_texture_memory_budget(&mut self, budget: Option<usize>) {
+        self.texture_memory_budget = budget;
+    }
 
-                        let canvas = surface.canvas();
+    pub fn texture_memory_budget(&self) -> Option<usize> {
+        self.texture_memory_budget
+    }
 
-                        canvas.draw_image(&old_image, Point::new(0.0, 0.0), None);
+    /// Evict the least recently used textures marked with [`Self::set_evictable`], except for
+    /// the font atlas, until at most `max_bytes` are held. Meant to be called between renders.
+    ///
+    /// Returns the number of evicted textures.
+    pub fn trim_textures(&mut self, max_bytes: usize) -> usize {
+        self.evict_textures(max_bytes, None)
+    }
 
-                        canvas.clip_rect(
-                            Rect::new(
-                                pos[0] as scalar,
-                                pos[1] as scalar,
-                                (pos[0] as i32 + delta_image.width()) as scalar,
-                                (pos[1] as i32 + delta_image.height()) as scalar,
-                            ),
-                            ClipOp::default(),
-                            false,
-                        );
+    /// Like [`Self::trim_textures`], but keeps the textures used in `first_frame` or later,
+    /// e.g. the ones drawn in a render that painted several surfaces.
+    pub(crate) fn trim_textures_since(&mut self, max_bytes: usize, first_frame: u64) -> usize {
+        self.evict_textures(max_bytes, Some(first_frame))
+    }
 
-                        canvas.clear(Color::TRANSPARENT);
-                        canvas.draw_image(
-                            &delta_image,
-                            Point::new(pos[0] as f32, pos[1] as f32),
-                            None,
-                        );
+    /// The frame of the next call to [`Self::paint_and_update_textures`].
+    pub(crate) fn next_frame(&self) -> u64 {
+        self.frame + 1
+    }
 
-                        surface.image_snapshot()
-                    }
-                };
+    /// Total number of textures evicted by this painter.
+    pub fn evictions(&self) -> usize {
+        self.evictions
+    }
 
-                let local_matrix = skia_safe::Matrix::scale((
-                    1.0 / image.width() as f32,
-                    1.0 / image.height() as f32,
-                ));
+    /// The textures evicted since the last call to this method.
+    ///
+    /// Only the last 1024 evicted textures are remembered, so a painter whose evictions are
+    /// never taken does not grow. [`crate::EguiSkia`] takes them after every paint.
+    pub fn take_evicted_textures(&mut self) -> Vec<TextureId> {
+        std::mem::take(&mut self.evicted)
+    }
 
-                let sampling_options = {
-                    use egui::TextureFilter;
-                    let filter_mode = match image_delta.options.magnification {
-                        TextureFilter::Nearest => skia_safe::FilterMode::Nearest,
-                        TextureFilter::Linear => skia_safe::FilterMode::Linear,
-                    };
-                    let mm_mode = match image_delta.options.minification {
-                        TextureFilter::Nearest => skia_safe::MipmapMode::Nearest,
-                        TextureFilter::Linear => skia_safe::MipmapMode::Linear,
-                    };
+    /// Evict evictable textures in least recently used order until at most `max_bytes` are
+    /// held. The font atlas is always kept, as are textures used in `keep_since` or later.
+    fn evict_textures(&mut self, max_bytes: usize, keep_since: Option<u64>) -> usize {
+        let mut bytes = self.texture_bytes();
+        if bytes <= max_bytes {
+            return 0;
+        }
 
-                    skia_safe::SamplingOptions::new(filter_mode, mm_mode)
-                };
-                let tile_mode = skia_safe::TileMode::Clamp;
+        let font_texture = TextureId::default();
+        let mut candidates: Vec<(u64, TextureId)> = self
+            .paints
+            .iter()
+            .filter(|(id, handle)| {
+                // Shared images are held by the store, evicting them frees nothing.
+                **id != font_texture
+                    && self.evictable.contains(id)
+                    && handle.byte_size() > 0
+                    && keep_since.is_none_or(|frame| handle.last_used < frame)
+            })
+            .map(|(id, handle)| (handle.last_used, *id))
+            .collect();
+        candidates.sort_unstable_by_key(|(last_used, _)| *last_used);
+
+        let mut evicted = 0;
+        for (_, id) in candidates {
+            if bytes <= max_bytes {
+                break;
+            }
+            if let Some(handle) = self.paints.remove(&id) {
+                self.evictable.remove(&id);
+                bytes = bytes.saturating_sub(handle.byte_size());
+                self.evicted.push(id);
+                evicted += 1;
+            }
+        }
+
+        let forgotten = self.evicted.len().saturating_sub(MAX_EVICTED_TEXTURES);
+        self.evicted.drain(..forgotten);
 
-                let font_shader = image
-                    .to_shader((tile_mode, tile_mode), sampling_options, &local_matrix)
-                    .unwrap();
+        self.evictions += evicted;
+        evicted
+    }
+
+    pub fn paint_and_update_textures(
+        &mut self,
+        canvas: &Canvas,
+        dpi: f32,
+        primitives: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+    ) -> RenderStats {
+        self.paint_tagged(canvas, dpi, primitives, textures_delta, &[])
+    }
 
-                image.width();
+    /// Like [`Self::paint_and_update_textures`], but marks each primitive with the id of the
+    /// innermost element of `pdf_nodes` containing it, for the structure tree of a PDF.
+    /// `pdf_nodes` holds element ids with the rects of their widgets, in points.
+    pub(crate) fn paint_tagged(
+        &mut self,
+        canvas: &Canvas,
+        dpi: f32,
+        primitives: Vec<ClippedPrimitive>,
+        mut textures_delta: TexturesDelta,
+        pdf_nodes: &[(i32, egui::Rect)],
+    ) -> RenderStats {
+        self.frame += 1;
 
-                let mut paint = Paint::default();
-                paint.set_shader(font_shader);
-                paint.set_color(Color::WHITE);
+        let mut stats = RenderStats {
+            clipped_primitives: primitives.len(),
+            ..Default::default()
+        };
 
-                self.paints.insert(*id, PaintHandle { paint, image });
+        let start = Instant::now();
+        for (id, image_deltas) in &textures_delta.set {
+            profile_scope!("upload texture");
+            for image_delta in image_deltas {
+                if image_delta.is_whole() {
+                    stats.textures_uploaded += 1;
+                } else {
+                    stats.textures_updated += 1;
+                }
+                self.set_texture(*id, image_delta);
             }
         }
+        stats.texture_upload_time = start.elapsed();
+
+        let mut debug_primitives = Vec::new();
+        // Encoded once per texture and paint.
+        let mut encoded_paints = AHashMap::new();
+
+        let start = Instant::now();
+
+        // Primitives are in points, everything below is drawn with the dpi scale applied.
+        let save_count = canvas.save();
+        canvas.scale((dpi, dpi));
 
+        // Marks the content drawn after it, 0 for none. Other canvases than the ones of PDF
+        // documents ignore it.
+        let mut pdf_node = 0;
         for primitive in primitives {
+            if !pdf_nodes.is_empty() {
+                let node_id = pdf_node_id(pdf_nodes, &primitive);
+                if node_id != pdf_node {
+                    skia_safe::pdf::set_node_id(canvas, node_id);
+                    pdf_node = node_id;
+                }
+            }
             let skclip_rect = Rect::new(
                 primitive.clip_rect.min.x,
                 primitive.clip_rect.min.y,
@@ -163,14 +545,23 @@ impl Painter {
             );
             match primitive.primitive {
                 Primitive::Mesh(mesh) => {
-                    canvas.set_matrix(skia_safe::M44::new_identity().set_scale(dpi, dpi, 1.0));
+                    profile_scope!("draw mesh");
                     let arc = skia_safe::AutoCanvasRestore::guard(canvas, true);
+                    arc.clip_rect(skclip_rect, ClipOp::default(), true);
 
                     let meshes = mesh.split_to_u16();
 
                     for mesh in &meshes {
                         let texture_id = mesh.texture_id;
 
+                        stats.meshes += 1;
+                        stats.vertices += mesh.vertices.len();
+                        stats.indices += mesh.indices.len();
+
+                        if self.debug_options.is_some() {
+                            debug_primitives.push(DebugPrimitive::mesh(skclip_rect, mesh));
+                        }
+
                         let mut pos = Vec::with_capacity(mesh.vertices.len());
                         let mut texs = Vec::with_capacity(mesh.vertices.len());
                         let mut colors = Vec::with_capacity(mesh.vertices.len());
@@ -240,42 +631,129 @@ impl Painter {
                             ),
                         );
 
-                        arc.clip_rect(skclip_rect, ClipOp::default(), true);
-
                         // Only render if the texture has been loaded
-                        if let Some(paint_handle) = self.paints.get(&texture_id) {
-                            arc.draw_vertices(&vertices, BlendMode::Modulate, &paint_handle.paint);
+                        if let Some(paint_handle) = self.paints.get_mut(&texture_id) {
+                            paint_handle.last_used = self.frame;
+                            let paint = if self.encode_textures {
+                                &*encoded_paints
+                                    .entry(texture_id)
+                                    .or_insert_with(|| paint_handle.encoded_paint())
+                            } else {
+                                &paint_handle.paint
+                            };
+                            arc.draw_vertices(&vertices, BlendMode::Modulate, paint);
                         }
                     }
                 }
                 Primitive::Callback(data) => {
+                    profile_scope!("paint callback");
                     let callback: Arc<EguiSkiaPaintCallback> = data.callback.downcast().unwrap();
                     let rect = data.rect;
+                    stats.callbacks += 1;
 
-                    let skia_rect = Rect::new(
-                        rect.min.x * dpi,
-                        rect.min.y * dpi,
-                        rect.max.x * dpi,
-                        rect.max.y * dpi,
-                    );
+                    if self.debug_options.is_some() {
+                        debug_primitives.push(DebugPrimitive::Callback {
+                            clip_rect: skclip_rect,
+                            rect: Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y),
+                        });
+                    }
 
-                    let mut drawable: Drawable =
-                        callback.callback.deref()(skia_rect).0.into_inner();
+                    let screen_size_px = self.screen_size_px.unwrap_or_else(|| {
+                        let size = canvas.base_layer_size();
+                        [size.width as u32, size.height as u32]
+                    });
+                    let info = PaintCallbackInfo {
+                        viewport: rect,
+                        clip_rect: primitive.clip_rect,
+                        pixels_per_point: dpi,
+                        screen_size_px,
+                    };
 
                     let mut arc = skia_safe::AutoCanvasRestore::guard(canvas, true);
 
                     arc.clip_rect(skclip_rect, ClipOp::default(), true);
                     arc.translate((rect.min.x, rect.min.y));
 
-                    drawable.draw(&mut arc, None);
+                    match &callback.callback {
+                        CallbackKind::Recorded(record) => {
+                            let mut drawable: Drawable = record.deref()(&info).0.into_inner();
+                            drawable.draw(&mut arc, None);
+                        }
+                        CallbackKind::Direct(draw) => draw.deref()(&arc, &info),
+                    }
                 }
             }
         }
 
+        if pdf_node != 0 {
+            skia_safe::pdf::set_node_id(canvas, 0);
+        }
+        canvas.restore_to_count(save_count);
+
+        stats.draw_time = start.elapsed();
+
+        if let Some(debug_options) = &self.debug_options {
+            if debug_options.separate_layer {
+                if let Some(mut surface) = raster_n32_premul(canvas.base_layer_size()) {
+                    // The layer covers the base layer, so it needs the transform of the canvas,
+                    // e.g. the translation of a crop or tile.
+                    let layer_canvas = surface.canvas();
+                    layer_canvas.set_matrix(&canvas.local_to_device());
+                    debug::draw_overlay(layer_canvas, dpi, debug_options, &debug_primitives);
+                    self.debug_layer = Some(surface.image_snapshot());
+                }
+            } else {
+                debug::draw_overlay(canvas, dpi, debug_options, &debug_primitives);
+            }
+        }
+
         textures_delta.free.iter().for_each(|id| {
-            self.paints.remove(id);
+            self.evictable.remove(id);
+            if self.paints.remove(id).is_some() {
+                stats.textures_freed += 1;
+            }
         });
         textures_delta.clear();
+
+        if let Some(budget) = self.texture_memory_budget {
+            stats.textures_evicted = self.evict_textures(budget, Some(self.frame));
+        }
+
+        stats.texture_bytes = self.texture_bytes();
+        stats
+    }
+
+    fn set_texture(&mut self, id: TextureId, image_delta: &ImageDelta) {
+        let ImageData::Color(color_image) = &image_delta.image;
+
+        match image_delta.pos {
+            None => {
+                let paint_handle = match &self.shared_textures {
+                    // The font atlas is updated all the time, sharing it would only add hashing.
+                    Some(store) if id != TextureId::default() => {
+                        PaintHandle::shared(store.get_or_insert(color_image), image_delta.options)
+                    }
+                    _ => PaintHandle::new(
+                        image_from_pixels(color_image.as_raw(), color_image.size),
+                        image_delta.options,
+                    ),
+                };
+                self.insert_texture(id, paint_handle);
+            }
+            Some(pos) => {
+                // Updates of textures that were never uploaded, or were evicted, are dropped.
+                let Some(paint_handle) = self.paints.remove(&id) else {
+                    return;
+                };
+                let paint_handle = paint_handle.write_region(pos, color_image, image_delta.options);
+                self.insert_texture(id, paint_handle);
+            }
+        }
+    }
+
+    fn insert_texture(&mut self, id: TextureId, mut paint_handle: PaintHandle) {
+        paint_handle.last_used = self.frame;
+        self.paints.insert(id, paint_handle);
     }
 }
 
@@ -286,23 +764,54 @@ impl Default for Painter {
 }
 
 pub struct EguiSkiaPaintCallback {
-    callback: Box<dyn Fn(Rect) -> SyncSendableDrawable + Send + Sync>,
+    callback: CallbackKind,
+}
+
+enum CallbackKind {
+    /// Records into a picture first, which is then replayed onto the target canvas.
+    Recorded(Box<dyn Fn(&PaintCallbackInfo) -> SyncSendableDrawable + Send + Sync>),
+    /// Draws straight onto the target canvas.
+    Direct(Box<dyn Fn(&Canvas, &PaintCallbackInfo) + Send + Sync>),
 }
 
 impl EguiSkiaPaintCallback {
+    /// The canvas passed to `callback` is in points, with the origin at the top left corner
+    /// of the callback's rect. The `pixels_per_point` scale is already applied, and drawing
+    /// is clipped to the clip rect of the callback.
     pub fn new<F: Fn(&Canvas) + Send + Sync + 'static>(callback: F) -> EguiSkiaPaintCallback {
+        Self::new_with_info(move |canvas, _info| callback(canvas))
+    }
+
+    /// Like [`Self::new`], but `callback` also receives the [`PaintCallbackInfo`] of the
+    /// callback: its rect and clip rect in points, `pixels_per_point` and the screen size
+    /// in pixels, so the drawing can adapt to the area allocated for it.
+    pub fn new_with_info<F: Fn(&Canvas, &PaintCallbackInfo) + Send + Sync + 'static>(
+        callback: F,
+    ) -> EguiSkiaPaintCallback {
         EguiSkiaPaintCallback {
-            callback: Box::new(move |rect| {
+            callback: CallbackKind::Recorded(Box::new(move |info| {
+                let bounds = Rect::from_wh(info.viewport.width(), info.viewport.height());
                 let mut pr = PictureRecorder::new();
-                let canvas = pr.begin_recording(rect, false);
-                callback(canvas);
+                let canvas = pr.begin_recording(bounds, false);
+                callback(canvas, info);
                 SyncSendableDrawable(
                     pr.finish_recording_as_drawable()
                         .unwrap()
                         .wrap_send()
                         .unwrap(),
                 )
-            }),
+            })),
+        }
+    }
+
+    /// Like [`Self::new_with_info`], but `callback` draws directly onto the target canvas
+    /// while painting, instead of being recorded into a picture and replayed. This avoids
+    /// doing the work twice for heavy drawings.
+    pub fn new_direct<F: Fn(&Canvas, &PaintCallbackInfo) + Send + Sync + 'static>(
+        callback: F,
+    ) -> EguiSkiaPaintCallback {
+        EguiSkiaPaintCallback {
+            callback: CallbackKind::Direct(Box::new(callback)),
         }
     }
 }
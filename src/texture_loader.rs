@@ -0,0 +1,70 @@
+use std::sync::Mutex;
+
+use ahash::AHashMap;
+use egui::load::{DefaultTextureLoader, SizeHint, TextureLoadResult, TextureLoader, TexturePoll};
+use egui::{Context, TextureId, TextureOptions};
+
+/// egui's [`DefaultTextureLoader`], which also remembers the uri of every texture it loaded.
+///
+/// Those textures can be evicted by the painter: after forgetting their uri, egui loads
+/// them again the next time they are shown. Textures egui does not own a loader for,
+/// e.g. ones created with [`Context::load_texture`], are never uploaded again.
+#[derive(Default)]
+pub(crate) struct TrackingTextureLoader {
+    loader: DefaultTextureLoader,
+    uris: Mutex<AHashMap<TextureId, String>>,
+}
+
+impl TrackingTextureLoader {
+    /// The uri `id` was loaded from, if it was loaded by this loader.
+    pub fn uri(&self, id: TextureId) -> Option<String> {
+        self.uris.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Called when egui frees `id`.
+    pub fn remove(&self, id: TextureId) {
+        self.uris.lock().unwrap().remove(&id);
+    }
+}
+
+impl TextureLoader for TrackingTextureLoader {
+    fn id(&self) -> &str {
+        egui::generate_loader_id!(TrackingTextureLoader)
+    }
+
+    fn load(
+        &self,
+        ctx: &Context,
+        uri: &str,
+        texture_options: TextureOptions,
+        size_hint: SizeHint,
+    ) -> TextureLoadResult {
+        let result = self.loader.load(ctx, uri, texture_options, size_hint);
+        if let Ok(TexturePoll::Ready { texture }) = &result {
+            self.uris
+                .lock()
+                .unwrap()
+                .entry(texture.id)
+                .or_insert_with(|| uri.to_owned());
+        }
+        result
+    }
+
+    fn forget(&self, uri: &str) {
+        self.loader.forget(uri);
+        self.uris.lock().unwrap().retain(|_, loaded| loaded != uri);
+    }
+
+    fn forget_all(&self) {
+        self.loader.forget_all();
+        self.uris.lock().unwrap().clear();
+    }
+
+    fn end_pass(&self, pass_index: u64) {
+        self.loader.end_pass(pass_index);
+    }
+
+    fn byte_size(&self) -> usize {
+        self.loader.byte_size()
+    }
+}
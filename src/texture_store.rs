@@ -0,0 +1,97 @@
+use std::sync::{Arc, Mutex};
+
+use ahash::{AHashMap, RandomState};
+use egui::ColorImage;
+use skia_safe::{ConditionallySend, Image};
+
+use crate::painter::image_from_pixels;
+
+/// A thread-safe store of texture images keyed by their content, which several
+/// [`crate::Painter`]s can share via [`crate::Painter::set_shared_texture_store`].
+///
+/// When a painter receives a texture from egui that is already in the store, it uses the
+/// stored image instead of creating its own, so images like logos that are shown by many
+/// renderers are only uploaded and held once. Decoding happens in egui's image loaders;
+/// install the same loader instances on every context to share that work as well.
+///
+/// Cloning the store is cheap and returns a handle to the same images.
+#[derive(Clone, Default)]
+pub struct SharedTextureStore {
+    inner: Arc<StoreInner>,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    hasher: RandomState,
+    images: Mutex<AHashMap<(u64, [usize; 2]), Image>>,
+}
+
+impl SharedTextureStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of images in the store.
+    pub fn len(&self) -> usize {
+        self.inner.images.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes of pixel memory held by the images in the store.
+    pub fn byte_size(&self) -> usize {
+        self.inner
+            .images
+            .lock()
+            .unwrap()
+            .values()
+            .map(|image| image.image_info().compute_min_byte_size())
+            .sum()
+    }
+
+    /// Remove the images no painter uses anymore, e.g. after the textures were freed by egui.
+    /// The store does not do this by itself, so call it from time to time to keep it from
+    /// growing.
+    ///
+    /// Returns the number of removed images.
+    pub fn purge_unused(&self) -> usize {
+        let mut images = self.inner.images.lock().unwrap();
+        let len = images.len();
+        // An image that can be sent to another thread is only referenced by the store.
+        images.retain(|_, image| !image.can_send());
+        len - images.len()
+    }
+
+    /// Remove all images from the store. Painters keep the images they already use.
+    pub fn clear(&self) {
+        self.inner.images.lock().unwrap().clear();
+    }
+
+    /// Returns the stored image with the same content as `color_image`, adding it if needed.
+    pub(crate) fn get_or_insert(&self, color_image: &ColorImage) -> Image {
+        let key = (
+            self.inner.hasher.hash_one(color_image.as_raw()),
+            color_image.size,
+        );
+
+        // Held until the image is inserted, so painters uploading the same image at the same
+        // time do not both add it.
+        let mut images = self.inner.images.lock().unwrap();
+        if let Some(image) = images.get(&key) {
+            // Guard against hash collisions.
+            let same_pixels = image
+                .peek_pixels()
+                .and_then(|pixmap| pixmap.bytes().map(|bytes| bytes == color_image.as_raw()))
+                .unwrap_or(false);
+            if same_pixels {
+                return image.clone();
+            }
+        }
+
+        let image = image_from_pixels(color_image.as_raw(), color_image.size);
+        images.insert(key, image.clone());
+        image
+    }
+}
@@ -0,0 +1,100 @@
+use egui::epaint::ClippedShape;
+use egui::{
+    Context, ImmediateViewport, OrderedViewportIdMap, Pos2, RawInput, Rect, TexturesDelta, Ui,
+    ViewportBuilder, ViewportId, ViewportIdPair, ViewportInfo, ViewportOutput,
+};
+
+/// The output of a viewport, kept in the memory of its context until
+/// [`crate::EguiSkia`] collects it after the frame.
+#[derive(Clone)]
+pub(crate) struct ViewportFrame {
+    pub id: ViewportId,
+    /// In points.
+    pub screen_rect: Rect,
+    pub pixels_per_point: f32,
+    pub shapes: Vec<ClippedShape>,
+    pub textures_delta: TexturesDelta,
+}
+
+fn frames_id() -> egui::Id {
+    egui::Id::new("smol_egui_skia_immediate_viewports")
+}
+
+/// Makes `ctx` run immediate viewports as separate frames instead of embedding them.
+/// Deferred viewports are not embedded either, see [`render_deferred_viewports`].
+///
+/// egui keeps the renderer per thread, so this has to be called on the thread running the ui.
+pub(crate) fn render_immediate_viewports(ctx: &Context) {
+    ctx.set_embed_viewports(false);
+    Context::set_immediate_viewport_renderer(render_immediate_viewport);
+}
+
+/// Runs the deferred viewports of `viewport_output` as separate frames. egui only leaves
+/// them to the backend when viewports are not embedded.
+pub(crate) fn render_deferred_viewports(
+    ctx: &Context,
+    viewport_output: &OrderedViewportIdMap<ViewportOutput>,
+) {
+    for (id, output) in viewport_output {
+        if let Some(viewport_ui_cb) = &output.viewport_ui_cb {
+            run_viewport(
+                ctx,
+                ViewportIdPair::from_self_and_parent(*id, output.parent),
+                &output.builder,
+                |ui| viewport_ui_cb(ui),
+            );
+        }
+    }
+}
+
+/// The frames of the viewports run since the last call, in the order they were run.
+pub(crate) fn take_frames(ctx: &Context) -> Vec<ViewportFrame> {
+    ctx.data_mut(|data| data.remove_temp::<Vec<ViewportFrame>>(frames_id()))
+        .unwrap_or_default()
+}
+
+fn render_immediate_viewport(ctx: &Context, mut viewport: ImmediateViewport<'_>) {
+    run_viewport(ctx, viewport.ids, &viewport.builder, |ui| {
+        (viewport.viewport_ui_cb)(ui)
+    });
+}
+
+fn run_viewport(
+    ctx: &Context,
+    ids: ViewportIdPair,
+    builder: &ViewportBuilder,
+    mut viewport_ui: impl FnMut(&mut Ui),
+) {
+    // Viewports are laid out at the scale of their parent, with the size they ask for.
+    let size = builder
+        .inner_size
+        .unwrap_or_else(|| ctx.viewport_rect().size());
+    let mut input = RawInput {
+        viewport_id: ids.this,
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, size)),
+        ..Default::default()
+    };
+    input.viewports.insert(
+        ids.this,
+        ViewportInfo {
+            parent: Some(ids.parent),
+            title: builder.title.clone(),
+            native_pixels_per_point: ctx.native_pixels_per_point(),
+            ..Default::default()
+        },
+    );
+
+    let output = ctx.run_ui(input, |ui| viewport_ui(ui));
+
+    let frame = ViewportFrame {
+        id: ids.this,
+        screen_rect: Rect::from_min_size(Pos2::ZERO, size),
+        pixels_per_point: output.pixels_per_point,
+        shapes: output.shapes,
+        textures_delta: output.textures_delta,
+    };
+    ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<Vec<ViewportFrame>>(frames_id())
+            .push(frame);
+    });
+}
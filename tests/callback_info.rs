@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex};
+
+use smol_egui_skia::{EguiSkiaPaintCallback, RasterizeOptions, rasterize_ui, rasterize_ui_tiled};
+
+/// A ui with a callback in its top left corner that records the screen size it is painted
+/// with into `screen_sizes`.
+fn ui_with_callback(screen_sizes: &Arc<Mutex<Vec<[u32; 2]>>>) -> impl FnMut(&mut egui::Ui) {
+    let screen_sizes = screen_sizes.clone();
+    move |ui| {
+        let screen_sizes = screen_sizes.clone();
+        let callback = EguiSkiaPaintCallback::new_with_info(move |_canvas, info| {
+            screen_sizes.lock().unwrap().push(info.screen_size_px);
+        });
+        ui.painter().add(egui::PaintCallback {
+            rect: egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(20.0, 20.0)),
+            callback: Arc::new(callback),
+        });
+    }
+}
+
+/// Callbacks get the size of the whole screen, also when only a part of it is painted.
+#[test]
+fn callbacks_get_the_screen_size() {
+    let screen_sizes = Arc::new(Mutex::new(Vec::new()));
+
+    rasterize_ui(
+        (200, 100),
+        ui_with_callback(&screen_sizes),
+        Some(RasterizeOptions {
+            pixels_per_point: 2.0,
+            crop: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(30.0, 30.0),
+            )),
+            ..Default::default()
+        }),
+    );
+    rasterize_ui_tiled(
+        (200, 100),
+        (64, 64),
+        ui_with_callback(&screen_sizes),
+        Some(RasterizeOptions {
+            pixels_per_point: 2.0,
+            ..Default::default()
+        }),
+        |_, _| {},
+    );
+
+    let screen_sizes = screen_sizes.lock().unwrap();
+    assert!(!screen_sizes.is_empty());
+    assert!(screen_sizes.iter().all(|size| *size == [200, 100]));
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use egui::epaint::Primitive;
+use egui::{ClippedPrimitive, PaintCallback, Pos2, Rect, TexturesDelta};
+use skia_safe::surfaces::raster_n32_premul;
+use skia_safe::{Color, Paint};
+use smol_egui_skia::{EguiSkiaPaintCallback, Painter};
+
+/// Paints a callback at several `pixels_per_point` values and checks that it ends up
+/// at the same place in points, clipped to its clip rect.
+#[test]
+fn callback_is_placed_in_points() {
+    for pixels_per_point in [1.0, 2.0, 1.5] {
+        let mut surface = raster_n32_premul((200, 200)).expect("Failed to create surface");
+        surface.canvas().clear(Color::WHITE);
+
+        // The callback fills more than its rect, the clip rect cuts off its right half.
+        let rect = Rect::from_min_max(Pos2::new(20.0, 30.0), Pos2::new(60.0, 70.0));
+        let clip_rect = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(40.0, 100.0));
+        let callback = EguiSkiaPaintCallback::new(|canvas| {
+            let mut paint = Paint::default();
+            paint.set_color(Color::RED);
+            canvas.draw_rect(skia_safe::Rect::from_wh(80.0, 40.0), &paint);
+        });
+
+        let mut painter = Painter::new();
+        painter.paint_and_update_textures(
+            surface.canvas(),
+            pixels_per_point,
+            vec![ClippedPrimitive {
+                clip_rect,
+                primitive: Primitive::Callback(PaintCallback {
+                    rect,
+                    callback: Arc::new(callback),
+                }),
+            }],
+            TexturesDelta::default(),
+        );
+
+        let pixmap = surface.peek_pixels().expect("Failed to read pixels");
+        let color_at = |x: f32, y: f32| {
+            pixmap.get_color(((x * pixels_per_point) as i32, (y * pixels_per_point) as i32))
+        };
+
+        // Inside the callback rect and the clip rect.
+        assert_eq!(color_at(21.0, 31.0), Color::RED, "at {pixels_per_point}x");
+        assert_eq!(color_at(39.0, 69.0), Color::RED);
+        // Above and left of the callback rect.
+        assert_eq!(color_at(19.0, 31.0), Color::WHITE);
+        assert_eq!(color_at(21.0, 29.0), Color::WHITE);
+        // Inside the callback rect, but outside of the clip rect.
+        assert_eq!(color_at(41.0, 31.0), Color::WHITE);
+        // Below the callback rect.
+        assert_eq!(color_at(30.0, 71.0), Color::WHITE);
+    }
+}
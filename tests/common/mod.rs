@@ -0,0 +1,59 @@
+//! Fixtures shared by the integration tests. Every test crate only uses some of them.
+#![allow(dead_code)]
+
+use egui::epaint::ImageDelta;
+use egui::{TextureId, TexturesDelta};
+use skia_safe::surfaces::raster_n32_premul;
+use skia_safe::{IRect, Surface};
+use smol_egui_skia::{EguiSkia, Painter};
+
+/// Input for a screen of `width` by `height` points.
+pub fn screen_input(width: f32, height: f32) -> egui::RawInput {
+    egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(width, height),
+        )),
+        ..Default::default()
+    }
+}
+
+/// Runs a single frame of `ui` at 1x on a screen of `size` points.
+pub fn run_frame(size: (i32, i32), ui: impl FnMut(&mut egui::Ui)) -> EguiSkia {
+    let mut backend = EguiSkia::new(1.0);
+    backend.run_ui(screen_input(size.0 as f32, size.1 as f32), ui);
+    backend
+}
+
+/// A texture delta that applies `delta` to the texture `id`.
+pub fn upload(id: TextureId, delta: ImageDelta) -> TexturesDelta {
+    let mut textures_delta = TexturesDelta::default();
+    textures_delta.set.insert(id, [delta].into_iter().collect());
+    textures_delta
+}
+
+/// Applies `textures_delta` without drawing anything.
+pub fn paint(painter: &mut Painter, textures_delta: TexturesDelta) {
+    let mut surface = raster_n32_premul((10, 10)).expect("Failed to create surface");
+    painter.paint_and_update_textures(surface.canvas(), 1.0, Vec::new(), textures_delta);
+}
+
+/// The pixels of `surface`, row by row.
+pub fn pixels(surface: &mut Surface) -> Vec<u8> {
+    let pixmap = surface.peek_pixels().expect("Failed to read pixels");
+    pixmap.bytes().expect("Pixels are not readable").to_vec()
+}
+
+/// The pixels of `surface` inside `rect`, row by row.
+pub fn region(surface: &mut Surface, rect: IRect) -> Vec<u8> {
+    let pixmap = surface.peek_pixels().expect("Failed to read pixels");
+    let row_bytes = pixmap.row_bytes();
+    let bytes = pixmap.bytes().expect("Pixels are not readable");
+    (rect.top..rect.bottom)
+        .flat_map(|y| {
+            let start = y as usize * row_bytes + rect.left as usize * 4;
+            &bytes[start..start + rect.width() as usize * 4]
+        })
+        .copied()
+        .collect()
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{pixels, region};
+use skia_safe::IRect;
+use smol_egui_skia::{RasterizeOptions, rasterize_ui};
+
+fn ui(ui: &mut egui::Ui) {
+    ui.heading("Cropped rendering");
+    for i in 0..6 {
+        ui.label(format!("Line {i} of text"));
+    }
+    let _ = ui.button("A button");
+}
+
+/// An empty crop still gets a surface of one pixel.
+#[test]
+fn empty_crop_is_one_pixel() {
+    let surface = rasterize_ui(
+        (100, 100),
+        |ui| {
+            ui.label("Hello");
+        },
+        Some(RasterizeOptions {
+            crop: Some(egui::Rect::from_min_size(
+                egui::pos2(10.0, 10.0),
+                egui::Vec2::ZERO,
+            )),
+            ..Default::default()
+        }),
+    );
+    assert_eq!((surface.width(), surface.height()), (1, 1));
+}
+
+/// A crop is the same image as the same region of a render of the whole screen.
+#[test]
+fn crop_matches_full_render() {
+    let size = (200, 150);
+    let pixels_per_point = 2.0;
+    let crop = egui::Rect::from_min_max(egui::pos2(15.0, 20.0), egui::pos2(70.0, 60.0));
+    let options = |crop| {
+        Some(RasterizeOptions {
+            pixels_per_point,
+            crop,
+            ..Default::default()
+        })
+    };
+
+    let mut full = rasterize_ui(size, ui, options(None));
+    let mut cropped = rasterize_ui(size, ui, options(Some(crop)));
+
+    let crop_px = IRect::from_ltrb(30, 40, 140, 120);
+    assert_eq!(
+        (cropped.width(), cropped.height()),
+        (crop_px.width(), crop_px.height())
+    );
+    assert_eq!(pixels(&mut cropped), region(&mut full, crop_px));
+}
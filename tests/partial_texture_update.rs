@@ -0,0 +1,87 @@
+mod common;
+
+use common::{paint, upload};
+use egui::epaint::{ImageDelta, Primitive};
+use egui::{ClippedPrimitive, Color32, ColorImage, Mesh, Pos2, Rect, TextureId, TextureOptions};
+use skia_safe::Color;
+use skia_safe::surfaces::raster_n32_premul;
+use smol_egui_skia::Painter;
+
+/// Applies `update` to `id` and draws the texture over a 40x40 surface, 10 pixels per texel,
+/// returning the color at the center of every texel, row by row.
+fn update_and_draw(painter: &mut Painter, id: TextureId, update: ImageDelta) -> [[Color; 4]; 4] {
+    let mut mesh = Mesh::with_texture(id);
+    mesh.add_rect_with_uv(
+        Rect::from_min_size(Pos2::ZERO, egui::vec2(40.0, 40.0)),
+        Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1.0, 1.0)),
+        Color32::WHITE,
+    );
+
+    let mut surface = raster_n32_premul((40, 40)).expect("Failed to create surface");
+    painter.paint_and_update_textures(
+        surface.canvas(),
+        1.0,
+        vec![ClippedPrimitive {
+            clip_rect: Rect::EVERYTHING,
+            primitive: Primitive::Mesh(mesh),
+        }],
+        upload(id, update),
+    );
+
+    let pixmap = surface.peek_pixels().expect("Failed to read pixels");
+    std::array::from_fn(|y| {
+        std::array::from_fn(|x| pixmap.get_color((x as i32 * 10 + 5, y as i32 * 10 + 5)))
+    })
+}
+
+/// Partial updates change the pixels inside their rect and keep the ones outside of it,
+/// also when a texture is updated more than once.
+#[test]
+fn partial_updates_write_into_the_texture() {
+    let id = TextureId::Managed(1);
+    let mut painter = Painter::new();
+    paint(
+        &mut painter,
+        upload(
+            id,
+            ImageDelta::full(
+                ColorImage::filled([4, 4], Color32::RED),
+                TextureOptions::NEAREST,
+            ),
+        ),
+    );
+    let bytes = painter.texture_bytes();
+
+    let texels = update_and_draw(
+        &mut painter,
+        id,
+        ImageDelta::partial(
+            [1, 1],
+            ColorImage::filled([2, 2], Color32::BLUE),
+            TextureOptions::NEAREST,
+        ),
+    );
+    for (y, row) in texels.iter().enumerate() {
+        for (x, color) in row.iter().enumerate() {
+            let inside = (1..3).contains(&x) && (1..3).contains(&y);
+            let expected = if inside { Color::BLUE } else { Color::RED };
+            assert_eq!(*color, expected, "texel ({x}, {y})");
+        }
+    }
+
+    let texels = update_and_draw(
+        &mut painter,
+        id,
+        ImageDelta::partial(
+            [0, 0],
+            ColorImage::filled([1, 1], Color32::GREEN),
+            TextureOptions::NEAREST,
+        ),
+    );
+    assert_eq!(texels[0][0], Color::GREEN);
+    assert_eq!(texels[1][1], Color::BLUE);
+    assert_eq!(texels[3][3], Color::RED);
+
+    // The updates are written into the texture instead of adding copies of it.
+    assert_eq!(painter.texture_bytes(), bytes);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::screen_input;
+use skia_safe::pdf::{self, Metadata};
+use smol_egui_skia::EguiSkia;
+
+/// Painting onto a PDF document with the structure of the frame marks the drawn content
+/// with the elements of the widgets.
+#[test]
+fn pdf_content_is_tagged() {
+    let mut backend = EguiSkia::new(1.0);
+    backend.egui_ctx.enable_accesskit();
+    for _ in 0..2 {
+        backend.run_ui(screen_input(200.0, 100.0), |ui| {
+            ui.label("Tagged label");
+            let _ = ui.button("Tagged button");
+        });
+    }
+
+    let tree = backend.accessibility_tree().expect("accesskit is enabled");
+    let metadata = Metadata {
+        structure_element_tree_root: Some(tree.pdf_structure()),
+        // Keeps the content streams readable.
+        compression_level: pdf::CompressionLevel::None,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    let mut document = pdf::new_document(&mut output, Some(&metadata)).begin_page((200, 100), None);
+    backend.paint(document.canvas());
+    document.end_page().close();
+
+    // Marked content is referenced by its id in the content stream.
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("/MCID"));
+}
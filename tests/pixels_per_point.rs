@@ -0,0 +1,55 @@
+use smol_egui_skia::{RasterizeOptions, rasterize_ui_with_geometry};
+
+/// Renders the same ui at 1x, 2x and 1.5x and checks that every widget ends up at the same
+/// place in points.
+#[test]
+fn layout_is_the_same_in_points() {
+    let size_in_points = (300.0, 200.0);
+
+    let mut layouts = Vec::new();
+    for pixels_per_point in [1.0, 2.0, 1.5] {
+        let size = (
+            (size_in_points.0 * pixels_per_point) as i32,
+            (size_in_points.1 * pixels_per_point) as i32,
+        );
+        let mut checked = true;
+        let (surface, geometry) = rasterize_ui_with_geometry(
+            size,
+            |ui| {
+                ui.heading("Pixels per point");
+                ui.checkbox(&mut checked, "Same layout at every scale");
+                let _ = ui.button("A button");
+                ui.with_layout(egui::Layout::bottom_up(egui::Align::Max), |ui| {
+                    ui.label("Bottom right");
+                });
+            },
+            Some(RasterizeOptions {
+                pixels_per_point,
+                ..Default::default()
+            }),
+        );
+        assert_eq!((surface.width(), surface.height()), size);
+
+        let mut layout: Vec<_> = geometry
+            .into_iter()
+            .map(|widget| (widget.id, widget.rect / pixels_per_point))
+            .collect();
+        layout.sort_by_key(|(id, _)| *id);
+        layouts.push((pixels_per_point, layout));
+    }
+
+    let (_, reference) = &layouts[0];
+    assert!(!reference.is_empty());
+    for (pixels_per_point, layout) in &layouts[1..] {
+        assert_eq!(layout.len(), reference.len(), "at {pixels_per_point}x");
+        for ((id, rect), (reference_id, reference_rect)) in layout.iter().zip(reference) {
+            assert_eq!(id, reference_id, "at {pixels_per_point}x");
+            // Text is snapped to pixels, so allow for a little rounding.
+            assert!(
+                (rect.min - reference_rect.min).length() < 1.0
+                    && (rect.max - reference_rect.max).length() < 1.0,
+                "widget {id} at {pixels_per_point}x is at {rect:?}, at 1x at {reference_rect:?}",
+            );
+        }
+    }
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::screen_input;
+use skia_safe::Color;
+use skia_safe::surfaces::raster_n32_premul;
+use smol_egui_skia::EguiSkia;
+
+fn filled(color: egui::Color32) -> impl FnMut(&mut egui::Ui) {
+    move |ui| {
+        ui.painter().rect_filled(ui.max_rect(), 0.0, color);
+    }
+}
+
+/// Paints the texture over the whole screen and returns the color at its center.
+fn paint_texture(backend: &mut EguiSkia, texture: egui::load::SizedTexture) -> Color {
+    backend.run_ui(screen_input(100.0, 100.0), |ui| {
+        ui.painter().image(
+            texture.id,
+            ui.max_rect(),
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+    });
+    let mut surface = raster_n32_premul((100, 100)).expect("Failed to create surface");
+    backend.paint(surface.canvas());
+    surface
+        .peek_pixels()
+        .expect("Failed to read pixels")
+        .get_color((50, 50))
+}
+
+/// Rasterizes a nested ui into a texture, updates it in place and frees it.
+#[test]
+fn nested_ui_texture_is_updated_and_freed() {
+    let mut backend = EguiSkia::new(1.0);
+
+    let texture =
+        backend.rasterize_ui_to_texture(egui::vec2(20.0, 20.0), filled(egui::Color32::RED), None);
+    assert_eq!(paint_texture(&mut backend, texture), Color::RED);
+
+    let updated = backend.rasterize_ui_to_texture(
+        egui::vec2(20.0, 20.0),
+        filled(egui::Color32::BLUE),
+        Some(texture.id),
+    );
+    assert_eq!(updated.id, texture.id);
+    assert_eq!(paint_texture(&mut backend, updated), Color::BLUE);
+
+    backend.painter.free_native_texture(texture.id);
+    assert!(!backend.painter.has_texture(&texture.id));
+}
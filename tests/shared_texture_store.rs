@@ -0,0 +1,71 @@
+mod common;
+
+use common::{paint, upload};
+use egui::epaint::ImageDelta;
+use egui::{Color32, ColorImage, TextureId, TextureOptions, TexturesDelta};
+use smol_egui_skia::{Painter, SharedTextureStore};
+
+/// Painters sharing a store hold identical images once, and the store drops them once
+/// no painter uses them anymore.
+#[test]
+fn unused_images_are_purged() {
+    let store = SharedTextureStore::new();
+    let id = TextureId::Managed(1);
+    let image = ColorImage::filled([16, 16], Color32::RED);
+
+    let mut painters = [Painter::new(), Painter::new()];
+    for painter in &mut painters {
+        painter.set_shared_texture_store(Some(store.clone()));
+        paint(
+            painter,
+            upload(id, ImageDelta::full(image.clone(), TextureOptions::LINEAR)),
+        );
+        // The pixels are owned by the store.
+        assert_eq!(painter.texture_bytes(), 0);
+    }
+    assert_eq!(store.len(), 1);
+
+    assert_eq!(store.purge_unused(), 0);
+    for painter in &mut painters {
+        let mut textures_delta = TexturesDelta::default();
+        textures_delta.free.push(id);
+        paint(painter, textures_delta);
+    }
+    assert_eq!(store.purge_unused(), 1);
+    assert!(store.is_empty());
+}
+
+/// Textures whose pixels are held by the store are not evicted, which would free nothing.
+#[test]
+fn shared_textures_are_not_evicted() {
+    let shared = TextureId::Managed(1);
+    let mut painter = Painter::new();
+    painter.set_shared_texture_store(Some(SharedTextureStore::new()));
+    paint(
+        &mut painter,
+        upload(
+            shared,
+            ImageDelta::full(
+                ColorImage::filled([16, 16], Color32::RED),
+                TextureOptions::LINEAR,
+            ),
+        ),
+    );
+    painter.set_evictable(shared, true);
+
+    // The font atlas is not shared, so the painter holds bytes over the budget.
+    paint(
+        &mut painter,
+        upload(
+            TextureId::default(),
+            ImageDelta::full(
+                ColorImage::filled([16, 16], Color32::WHITE),
+                TextureOptions::LINEAR,
+            ),
+        ),
+    );
+    assert!(painter.texture_bytes() > 0);
+
+    assert_eq!(painter.trim_textures(0), 0);
+    assert!(painter.has_texture(&shared));
+}
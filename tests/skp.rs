@@ -0,0 +1,43 @@
+mod common;
+
+use common::pixels;
+use skia_safe::surfaces::raster_n32_premul;
+use skia_safe::{Color, Picture};
+use smol_egui_skia::EguiSkia;
+
+const SIZE: (i32, i32) = (200, 100);
+
+fn run_frame() -> EguiSkia {
+    common::run_frame(SIZE, |ui| {
+        ui.heading("Text is drawn from the font atlas");
+        let _ = ui.button("A button");
+    })
+}
+
+/// A frame loaded from `.skp` bytes looks like the frame painted directly, including the
+/// text, which is drawn from the font atlas texture.
+#[test]
+fn skp_round_trip_keeps_textures() {
+    let mut painted = raster_n32_premul(SIZE).expect("Failed to create surface");
+    painted.canvas().clear(Color::TRANSPARENT);
+    run_frame().paint(painted.canvas());
+
+    let skp = run_frame().paint_to_skp();
+    let picture = Picture::from_data(&skp).expect("Failed to load picture");
+    let mut replayed = raster_n32_premul(SIZE).expect("Failed to create surface");
+    replayed.canvas().clear(Color::TRANSPARENT);
+    picture.playback(replayed.canvas());
+
+    let painted = pixels(&mut painted);
+    let replayed = pixels(&mut replayed);
+    assert_eq!(painted.len(), replayed.len());
+
+    // Dropped textures would leave out the text. The textures go through PNG, which stores
+    // unpremultiplied colors, so allow for rounding.
+    let differences = painted
+        .iter()
+        .zip(&replayed)
+        .filter(|(a, b)| a.abs_diff(**b) > 2)
+        .count();
+    assert_eq!(differences, 0);
+}
@@ -0,0 +1,19 @@
+mod common;
+
+use common::run_frame;
+use egui::TextureId;
+use skia_safe::surfaces::raster_n32_premul;
+
+/// Painting no widgets keeps the texture delta of the frame for the next paint.
+#[test]
+fn paint_widgets_without_widgets_keeps_textures() {
+    let mut backend = run_frame((100, 100), |ui| {
+        ui.label("Hello");
+    });
+    assert!(backend.paint_widgets(&[], 0.0).is_empty());
+    assert!(!backend.painter.has_texture(&TextureId::default()));
+
+    let mut surface = raster_n32_premul((100, 100)).expect("Failed to create surface");
+    backend.paint(surface.canvas());
+    assert!(backend.painter.has_texture(&TextureId::default()));
+}
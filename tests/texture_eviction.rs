@@ -0,0 +1,99 @@
+mod common;
+
+use common::{paint, screen_input, upload};
+use egui::epaint::ImageDelta;
+use egui::{Color32, ColorImage, TextureId, TextureOptions, TexturesDelta};
+use skia_safe::surfaces::raster_n32_premul;
+use smol_egui_skia::{EguiSkia, Painter};
+
+fn image() -> ColorImage {
+    ColorImage::filled([16, 16], Color32::RED)
+}
+
+/// Only textures marked as evictable are evicted to stay within the memory budget.
+#[test]
+fn only_evictable_textures_are_evicted() {
+    let kept = TextureId::Managed(1);
+    let evictable = TextureId::Managed(2);
+
+    let mut painter = Painter::new();
+    paint(
+        &mut painter,
+        upload(kept, ImageDelta::full(image(), TextureOptions::LINEAR)),
+    );
+    paint(
+        &mut painter,
+        upload(evictable, ImageDelta::full(image(), TextureOptions::LINEAR)),
+    );
+    painter.set_evictable(evictable, true);
+
+    assert_eq!(painter.trim_textures(0), 1);
+    assert!(painter.has_texture(&kept));
+    assert!(!painter.has_texture(&evictable));
+    assert_eq!(painter.take_evicted_textures(), vec![evictable]);
+}
+
+/// Partial updates of textures that are not uploaded are dropped.
+#[test]
+fn partial_update_of_missing_texture_is_ignored() {
+    let mut painter = Painter::new();
+    paint(
+        &mut painter,
+        upload(
+            TextureId::Managed(1),
+            ImageDelta::partial([0, 0], image(), TextureOptions::LINEAR),
+        ),
+    );
+    assert!(!painter.has_texture(&TextureId::Managed(1)));
+}
+
+/// Renders that paint several surfaces keep the textures they drew within the memory budget,
+/// so repeating the render does not have to restore them.
+#[test]
+fn textures_drawn_in_tiled_render_are_kept() {
+    let mut backend = EguiSkia::new(1.0);
+    backend.painter.set_texture_memory_budget(Some(0));
+    let image = raster_n32_premul((16, 16))
+        .expect("Failed to create surface")
+        .image_snapshot();
+    let id = backend
+        .painter
+        .register_native_texture(image, TextureOptions::LINEAR);
+    backend.painter.set_evictable(id, true);
+
+    for _ in 0..2 {
+        backend.run_ui(screen_input(200.0, 200.0), |ui| {
+            ui.painter().image(
+                id,
+                egui::Rect::from_min_size(egui::pos2(100.0, 100.0), egui::vec2(50.0, 50.0)),
+                egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                Color32::WHITE,
+            );
+        });
+        backend.paint_tiled((200, 200), (64, 64), |_, _| {});
+        assert!(backend.painter.has_texture(&id));
+    }
+    assert_eq!(backend.painter.evictions(), 0);
+}
+
+/// Painters only remember the last evicted textures, so they do not grow when nobody takes
+/// them.
+#[test]
+fn evicted_textures_are_capped() {
+    let mut painter = Painter::new();
+    let mut textures_delta = TexturesDelta::default();
+    for i in 1..=1100 {
+        let id = TextureId::Managed(i);
+        textures_delta.set.insert(
+            id,
+            [ImageDelta::full(image(), TextureOptions::LINEAR)]
+                .into_iter()
+                .collect(),
+        );
+        painter.set_evictable(id, true);
+    }
+    paint(&mut painter, textures_delta);
+
+    assert_eq!(painter.trim_textures(0), 1100);
+    assert_eq!(painter.take_evicted_textures().len(), 1024);
+}
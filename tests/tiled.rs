@@ -0,0 +1,32 @@
+mod common;
+
+use common::{pixels, region};
+use smol_egui_skia::{rasterize_ui, rasterize_ui_tiled};
+
+fn ui(ui: &mut egui::Ui) {
+    ui.heading("Tiled rendering");
+    for i in 0..8 {
+        ui.label(format!("Line {i} of text that crosses tile borders"));
+    }
+    let _ = ui.button("A button");
+    ui.painter().circle_filled(
+        egui::pos2(150.0, 100.0),
+        30.0,
+        egui::Color32::from_rgb(200, 80, 40),
+    );
+}
+
+/// The tiles of a tiled render, put together, are the same image as a render into a single
+/// surface.
+#[test]
+fn tiles_match_single_surface() {
+    let size = (200, 150);
+    let mut full = rasterize_ui(size, ui, None);
+
+    let mut covered = 0;
+    rasterize_ui_tiled(size, (64, 48), ui, None, |tile, surface| {
+        assert_eq!(pixels(surface), region(&mut full, tile), "tile {tile:?}");
+        covered += tile.width() * tile.height();
+    });
+    assert_eq!(covered, size.0 * size.1);
+}
@@ -0,0 +1,40 @@
+use egui::{ViewportBuilder, ViewportId};
+use smol_egui_skia::{RasterizeOptions, rasterize_viewports};
+
+/// Immediate and deferred viewports are both rendered into surfaces of their own.
+#[test]
+fn viewports_get_their_own_surfaces() {
+    let immediate = ViewportId::from_hash_of("immediate");
+    let deferred = ViewportId::from_hash_of("deferred");
+
+    let surfaces = rasterize_viewports(
+        (400, 300),
+        |ui| {
+            ui.heading("Root viewport");
+            ui.ctx().show_viewport_immediate(
+                immediate,
+                ViewportBuilder::default().with_inner_size([200.0, 100.0]),
+                |ui, _class| {
+                    ui.label("Immediate");
+                },
+            );
+            ui.ctx().show_viewport_deferred(
+                deferred,
+                ViewportBuilder::default().with_inner_size([100.0, 50.0]),
+                |ui, _class| {
+                    ui.label("Deferred");
+                },
+            );
+        },
+        Some(RasterizeOptions {
+            pixels_per_point: 2.0,
+            ..Default::default()
+        }),
+    );
+
+    assert_eq!(surfaces.len(), 3);
+    let size = |id| (surfaces[&id].width(), surfaces[&id].height());
+    assert_eq!(size(ViewportId::ROOT), (400, 300));
+    assert_eq!(size(immediate), (400, 200));
+    assert_eq!(size(deferred), (200, 100));
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::screen_input;
+use smol_egui_skia::{EguiSkia, tag_widget};
+
+/// Widget rects are the ones of the last frame, also when widgets moved since the frame
+/// before.
+#[test]
+fn widget_rects_are_from_the_last_frame() {
+    let mut backend = EguiSkia::new(1.0);
+    backend.egui_ctx.enable_accesskit();
+    let tag = egui::Id::new("tagged label");
+
+    let mut last = None;
+    for space in [0.0, 50.0] {
+        backend.run_ui(screen_input(200.0, 200.0), |ui| {
+            ui.add_space(space);
+            let button = ui.button("Moving button");
+            let label = ui.label("Moving label");
+            tag_widget(&label, tag);
+            last = Some((button.id, button.rect, label.rect));
+        });
+    }
+
+    let (button_id, button_rect, label_rect) = last.unwrap();
+    assert_eq!(backend.widget_rect(button_id), Some(button_rect));
+    assert_eq!(backend.widget_rect(tag), Some(label_rect));
+}
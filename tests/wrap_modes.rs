@@ -0,0 +1,61 @@
+use egui::epaint::{ImageDelta, Primitive};
+use egui::{
+    ClippedPrimitive, Color32, ColorImage, Mesh, Pos2, Rect, TextureId, TextureOptions,
+    TextureWrapMode, TexturesDelta, vec2,
+};
+use skia_safe::Color;
+use skia_safe::surfaces::raster_n32_premul;
+use smol_egui_skia::Painter;
+
+/// Draws a red/blue texture with UVs from 0 to 3 once per wrap mode and checks a few
+/// pixels of each strip.
+#[test]
+fn wrap_modes_map_to_tile_modes() {
+    let texture = ColorImage::new([2, 1], vec![Color32::RED, Color32::BLUE]);
+
+    // Each texel is 20 points wide, one repetition of the texture 40 points.
+    // The expected colors are sampled at u = 1.25 and u = 1.75.
+    let strips = [
+        (TextureWrapMode::ClampToEdge, [Color::BLUE, Color::BLUE]),
+        (TextureWrapMode::Repeat, [Color::RED, Color::BLUE]),
+        (TextureWrapMode::MirroredRepeat, [Color::BLUE, Color::RED]),
+    ];
+
+    let mut textures_delta = TexturesDelta::default();
+    let mut primitives = Vec::new();
+    for (i, (wrap_mode, _)) in strips.iter().enumerate() {
+        let id = TextureId::Managed(i as u64 + 1);
+        let options = TextureOptions {
+            wrap_mode: *wrap_mode,
+            ..TextureOptions::NEAREST
+        };
+        textures_delta.set.insert(
+            id,
+            [ImageDelta::full(texture.clone(), options)]
+                .into_iter()
+                .collect(),
+        );
+
+        let mut mesh = Mesh::with_texture(id);
+        mesh.add_rect_with_uv(
+            Rect::from_min_size(Pos2::new(0.0, i as f32 * 50.0), vec2(120.0, 40.0)),
+            Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(3.0, 1.0)),
+            Color32::WHITE,
+        );
+        primitives.push(ClippedPrimitive {
+            clip_rect: Rect::EVERYTHING,
+            primitive: Primitive::Mesh(mesh),
+        });
+    }
+
+    let mut surface = raster_n32_premul((120, 140)).expect("Failed to create surface");
+    surface.canvas().clear(Color::WHITE);
+    Painter::new().paint_and_update_textures(surface.canvas(), 1.0, primitives, textures_delta);
+
+    let pixmap = surface.peek_pixels().expect("Failed to read pixels");
+    for (i, (wrap_mode, expected)) in strips.iter().enumerate() {
+        let y = i as i32 * 50 + 20;
+        assert_eq!(pixmap.get_color((50, y)), expected[0], "{wrap_mode:?}");
+        assert_eq!(pixmap.get_color((70, y)), expected[1], "{wrap_mode:?}");
+    }
+}